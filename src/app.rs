@@ -0,0 +1,35 @@
+use crate::init::State;
+use winit::{dpi::PhysicalSize, event::WindowEvent};
+
+/// An application driven by the event loop.
+///
+/// Only [`App::update`] is required, every other hook does nothing by default.
+pub trait App {
+    /// Called once after the renderer is set up, before the first frame.
+    fn init(&mut self, _state: &mut State) {}
+
+    /// Called every frame before rendering, `dt` is the time since the
+    /// previous update in seconds. Software rendering into the pixel buffer
+    /// happens here.
+    fn update(&mut self, state: &mut State, dt: f64);
+
+    /// Returns true if the main loop won't process the event any further.
+    fn input(&mut self, _state: &mut State, _event: &WindowEvent) -> bool {
+        false
+    }
+
+    /// Called after the surface has been resized, `size` is in physical pixels.
+    fn resize(&mut self, _state: &mut State, _size: PhysicalSize<u32>) {}
+
+    /// Called when the window gains or loses focus.
+    fn focus(&mut self, _state: &mut State, _focused: bool) {}
+
+    /// Called when the application is suspended by the platform.
+    fn suspend(&mut self, _state: &mut State) {}
+
+    /// Called when the application is resumed by the platform.
+    fn resume(&mut self, _state: &mut State) {}
+
+    /// Called once when the event loop is shutting down.
+    fn exit(&mut self, _state: &mut State) {}
+}
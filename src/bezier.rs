@@ -0,0 +1,325 @@
+use std::mem;
+
+use crate::{
+    canvas::Canvas, color::Color, gradient::Paint, polygon::FillRule, transform::Transform,
+};
+
+// furthest a flattened curve may stray from the real one, in canvas pixels
+const TOLERANCE: f32 = 0.25;
+// subdivision stops here even if the curve isn't flat yet
+const MAX_DEPTH: u32 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Segment {
+    MoveTo((f32, f32)),
+    LineTo((f32, f32)),
+    QuadTo((f32, f32), (f32, f32)),
+    CubicTo((f32, f32), (f32, f32), (f32, f32)),
+    Close,
+}
+
+/// Outline built from lines and Bezier curves, made of one or more
+/// subpaths. Curves are flattened into lines when the path is drawn, after
+/// the canvas transform, so they stay smooth at any scale.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path {
+    segments: Vec<Segment>,
+}
+
+// a flattened subpath in canvas coordinates
+struct Contour {
+    points: Vec<(f32, f32)>,
+    closed: bool,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a new subpath at `x, y`.
+    pub fn move_to(mut self, x: f32, y: f32) -> Self {
+        self.segments.push(Segment::MoveTo((x, y)));
+        self
+    }
+
+    pub fn line_to(mut self, x: f32, y: f32) -> Self {
+        self.segments.push(Segment::LineTo((x, y)));
+        self
+    }
+
+    /// Quadratic curve to `x, y` bent towards the control point `cx, cy`.
+    pub fn quad_to(mut self, cx: f32, cy: f32, x: f32, y: f32) -> Self {
+        self.segments.push(Segment::QuadTo((cx, cy), (x, y)));
+        self
+    }
+
+    /// Cubic curve to `x, y` with two control points.
+    pub fn cubic_to(mut self, c1x: f32, c1y: f32, c2x: f32, c2y: f32, x: f32, y: f32) -> Self {
+        self.segments
+            .push(Segment::CubicTo((c1x, c1y), (c2x, c2y), (x, y)));
+        self
+    }
+
+    /// Closes the current subpath with a line back to its start.
+    pub fn close(mut self) -> Self {
+        self.segments.push(Segment::Close);
+        self
+    }
+
+    // curves are affine invariant, so transforming the control points and
+    // flattening afterwards gives the tolerance in canvas pixels
+    fn contours(&self, transform: &Transform) -> Vec<Contour> {
+        let mut contours = Vec::new();
+        let mut points: Vec<(f32, f32)> = Vec::new();
+        let mut start = transform.apply(0.0, 0.0);
+        let apply = |(x, y): (f32, f32)| transform.apply(x, y);
+
+        for &segment in &self.segments {
+            if points.is_empty() && !matches!(segment, Segment::MoveTo(_) | Segment::Close) {
+                points.push(start);
+            }
+            match segment {
+                Segment::MoveTo(p) => {
+                    if points.len() > 1 {
+                        contours.push(Contour {
+                            points: mem::take(&mut points),
+                            closed: false,
+                        });
+                    }
+                    start = apply(p);
+                    points = vec![start];
+                }
+                Segment::LineTo(p) => points.push(apply(p)),
+                Segment::QuadTo(c, p) => {
+                    let from = points[points.len() - 1];
+                    flatten_quad(from, apply(c), apply(p), &mut points, 0);
+                }
+                Segment::CubicTo(c1, c2, p) => {
+                    let from = points[points.len() - 1];
+                    flatten_cubic(from, apply(c1), apply(c2), apply(p), &mut points, 0);
+                }
+                Segment::Close => {
+                    if points.len() > 1 {
+                        contours.push(Contour {
+                            points: mem::take(&mut points),
+                            closed: true,
+                        });
+                    }
+                    points.clear();
+                }
+            }
+        }
+        if points.len() > 1 {
+            contours.push(Contour {
+                points,
+                closed: false,
+            });
+        }
+        contours
+    }
+}
+
+impl Canvas<'_> {
+    /// One pixel wide quadratic curve from `p0` to `p2`.
+    pub fn quad_bezier(&mut self, p0: (f32, f32), p1: (f32, f32), p2: (f32, f32), color: Color) {
+        let path = Path::new()
+            .move_to(p0.0, p0.1)
+            .quad_to(p1.0, p1.1, p2.0, p2.1);
+        self.stroke_path(&path, 1, color);
+    }
+
+    /// One pixel wide cubic curve from `p0` to `p3`.
+    pub fn cubic_bezier(
+        &mut self,
+        p0: (f32, f32),
+        p1: (f32, f32),
+        p2: (f32, f32),
+        p3: (f32, f32),
+        color: Color,
+    ) {
+        let path = Path::new()
+            .move_to(p0.0, p0.1)
+            .cubic_to(p1.0, p1.1, p2.0, p2.1, p3.0, p3.1);
+        self.stroke_path(&path, 1, color);
+    }
+
+    /// Outlines every subpath, `width` pixels wide with rounded joints.
+    pub fn stroke_path(&mut self, path: &Path, width: u32, color: Color) {
+        let transform = self.transform();
+        let width = width as f32 * transform.length_scale();
+        for contour in path.contours(&transform) {
+            if width <= 1.0 {
+                let points: Vec<_> = contour
+                    .points
+                    .iter()
+                    .map(|&(x, y)| (x.floor() as i32, y.floor() as i32))
+                    .collect();
+                self.device_polyline(&points, contour.closed, color);
+            } else {
+                self.device_thick_polyline(&contour.points, contour.closed, width, color);
+            }
+        }
+    }
+
+    /// Fills the area enclosed by the path, every subpath is closed first.
+    /// Overlapping subpaths combine according to `rule`, so an inner one can
+    /// cut a hole.
+    pub fn fill_path(&mut self, path: &Path, rule: FillRule, paint: impl Into<Paint>) {
+        let contours = path.contours(&self.transform());
+        let contours: Vec<&[(f32, f32)]> = contours.iter().map(|c| &c.points[..]).collect();
+        self.with_paint(paint.into(), |canvas, color| {
+            canvas.device_contours(&contours, rule, color);
+        });
+    }
+}
+
+// adds the points of a flattened quadratic curve after `p0`
+fn flatten_quad(
+    p0: (f32, f32),
+    p1: (f32, f32),
+    p2: (f32, f32),
+    out: &mut Vec<(f32, f32)>,
+    depth: u32,
+) {
+    if depth >= MAX_DEPTH || distance_to_chord(p1, p0, p2) <= TOLERANCE {
+        out.push(p2);
+        return;
+    }
+    // de Casteljau split at the middle
+    let (a, b) = (mid(p0, p1), mid(p1, p2));
+    let m = mid(a, b);
+    flatten_quad(p0, a, m, out, depth + 1);
+    flatten_quad(m, b, p2, out, depth + 1);
+}
+
+// adds the points of a flattened cubic curve after `p0`
+fn flatten_cubic(
+    p0: (f32, f32),
+    p1: (f32, f32),
+    p2: (f32, f32),
+    p3: (f32, f32),
+    out: &mut Vec<(f32, f32)>,
+    depth: u32,
+) {
+    let flat = distance_to_chord(p1, p0, p3).max(distance_to_chord(p2, p0, p3)) <= TOLERANCE;
+    if depth >= MAX_DEPTH || flat {
+        out.push(p3);
+        return;
+    }
+    let (a, b, c) = (mid(p0, p1), mid(p1, p2), mid(p2, p3));
+    let (d, e) = (mid(a, b), mid(b, c));
+    let m = mid(d, e);
+    flatten_cubic(p0, a, d, m, out, depth + 1);
+    flatten_cubic(m, e, c, p3, out, depth + 1);
+}
+
+fn mid(a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+}
+
+// distance of a control point from the straight segment between both ends,
+// the curve stays within this of its chord
+fn distance_to_chord(p: (f32, f32), from: (f32, f32), to: (f32, f32)) -> f32 {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        (((p.0 - from.0) * dx + (p.1 - from.1) * dy) / length_squared).clamp(0.0, 1.0)
+    };
+    (p.0 - from.0 - t * dx).hypot(p.1 - from.1 - t * dy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        canvas::tests::{buffer, painted},
+        format::PixelFormat,
+    };
+
+    const RED: Color = Color::rgb(255, 0, 0);
+
+    fn quad_point(p0: (f32, f32), p1: (f32, f32), p2: (f32, f32), t: f32) -> (f32, f32) {
+        let u = 1.0 - t;
+        let at = |a: f32, b: f32, c: f32| u * u * a + 2.0 * u * t * b + t * t * c;
+        (at(p0.0, p1.0, p2.0), at(p0.1, p1.1, p2.1))
+    }
+
+    #[test]
+    fn flattened_curves_stay_within_the_tolerance() {
+        let (p0, p1, p2) = ((0.0, 0.0), (50.0, 100.0), (100.0, 0.0));
+        let mut points = vec![p0];
+        flatten_quad(p0, p1, p2, &mut points, 0);
+        assert!(points.len() > 4);
+        assert_eq!(points.last(), Some(&p2));
+        // the curve is never further than the tolerance from the lines
+        for i in 0..=1000 {
+            let p = quad_point(p0, p1, p2, i as f32 / 1000.0);
+            let distance = points
+                .windows(2)
+                .map(|line| distance_to_chord(p, line[0], line[1]))
+                .fold(f32::INFINITY, f32::min);
+            assert!(distance <= TOLERANCE, "{p:?} is {distance} away");
+        }
+
+        let mut points = vec![p0];
+        flatten_cubic(p0, (30.0, 0.0), (60.0, 0.0), (90.0, 0.0), &mut points, 0);
+        assert_eq!(points, [p0, (90.0, 0.0)]);
+    }
+
+    #[test]
+    fn subpaths_become_separate_contours() {
+        let path = Path::new()
+            .line_to(4.0, 0.0)
+            .move_to(1.0, 1.0)
+            .line_to(2.0, 1.0)
+            .line_to(2.0, 2.0)
+            .close()
+            .move_to(9.0, 9.0)
+            .quad_to(10.0, 9.0, 10.0, 10.0);
+        let contours = path.contours(&Transform::translate(1.0, 0.0));
+        let shapes: Vec<_> = contours.iter().map(|c| (c.points[0], c.closed)).collect();
+        assert_eq!(
+            shapes,
+            [
+                ((1.0, 0.0), false),
+                ((2.0, 1.0), true),
+                ((10.0, 9.0), false)
+            ]
+        );
+        assert_eq!(contours[1].points.len(), 3);
+    }
+
+    #[test]
+    fn inner_subpaths_cut_holes() {
+        let fill = |inner_clockwise: bool, rule: FillRule| {
+            let inner = if inner_clockwise {
+                [(2.0, 2.0), (4.0, 2.0), (4.0, 4.0), (2.0, 4.0)]
+            } else {
+                [(2.0, 2.0), (2.0, 4.0), (4.0, 4.0), (4.0, 2.0)]
+            };
+            let path = Path::new()
+                .move_to(0.0, 0.0)
+                .line_to(6.0, 0.0)
+                .line_to(6.0, 6.0)
+                .line_to(0.0, 6.0)
+                .close();
+            let path = inner
+                .iter()
+                .skip(1)
+                .fold(path.move_to(inner[0].0, inner[0].1), |path, &(x, y)| {
+                    path.line_to(x, y)
+                })
+                .close();
+            let mut pixels = buffer(6, 6, PixelFormat::Rgba8Srgb);
+            let mut canvas = Canvas::new(&mut pixels, 6, 6);
+            canvas.fill_path(&path, rule, RED);
+            painted(&canvas, RED).len()
+        };
+        assert_eq!(fill(true, FillRule::EvenOdd), 32);
+        assert_eq!(fill(false, FillRule::NonZero), 32);
+        assert_eq!(fill(true, FillRule::NonZero), 36);
+    }
+}
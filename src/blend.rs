@@ -0,0 +1,196 @@
+use crate::color::Color;
+
+/// How a drawn color is combined with the pixel already in the buffer.
+///
+/// Every mode is weighted by the source alpha, so a half transparent color
+/// applies half of the effect, and by the destination alpha, so drawing on a
+/// transparent pixel leaves the source color. Channels are combined as
+/// stored, in sRGB, with straight alpha.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    /// Overwrite the pixel, alpha included.
+    Replace,
+    /// Regular alpha compositing of the source over the destination.
+    #[default]
+    SourceOver,
+    /// Add the source to the destination, saturating at white.
+    Add,
+    /// Subtract the source from the destination, saturating at black.
+    Subtract,
+    /// Multiply the channels, darkens.
+    Multiply,
+    /// Inverse multiply of the inverted channels, lightens.
+    Screen,
+    /// Bitwise XOR of the color channels, drawing twice restores the pixel.
+    Xor,
+}
+
+impl BlendMode {
+    /// Combines `src` with `dst`, `coverage` scales the source alpha and is
+    /// used by anti-aliased edges.
+    pub fn blend(self, src: Color, dst: Color, coverage: u8) -> Color {
+        let alpha = mul(src.a, coverage);
+        if self == BlendMode::Replace {
+            return if coverage == 255 {
+                src
+            } else {
+                // mix the premultiplied colors so transparent pixels don't
+                // bleed their color into the result
+                let weights = (dst.a as u32 * (255 - coverage as u32), alpha as u32 * 255);
+                let channel = |s: u8, d: u8| mix(d, s, weights);
+                Color::rgba(
+                    channel(src.r, dst.r),
+                    channel(src.g, dst.g),
+                    channel(src.b, dst.b),
+                    lerp(dst.a, src.a, coverage),
+                )
+            };
+        }
+        if alpha == 0 {
+            return dst;
+        }
+
+        // the mode applied to an opaque destination
+        let channel = |s: u8, d: u8| -> u8 {
+            match self {
+                BlendMode::Replace | BlendMode::SourceOver => lerp(d, s, alpha),
+                BlendMode::Add => d.saturating_add(mul(s, alpha)),
+                BlendMode::Subtract => d.saturating_sub(mul(s, alpha)),
+                BlendMode::Multiply => lerp(d, mul(s, d), alpha),
+                BlendMode::Screen => lerp(d, 255 - mul(255 - s, 255 - d), alpha),
+                BlendMode::Xor => lerp(d, s ^ d, alpha),
+            }
+        };
+        let a = alpha + mul(dst.a, 255 - alpha);
+        if dst.a == 255 {
+            return Color::rgba(
+                channel(src.r, dst.r),
+                channel(src.g, dst.g),
+                channel(src.b, dst.b),
+                a,
+            );
+        }
+        // where the destination is transparent the source shows unchanged,
+        // straight alpha weighted by how much of each is there
+        let weights = (alpha as u32 * (255 - dst.a as u32), dst.a as u32 * 255);
+        let channel = |s: u8, d: u8| mix(s, channel(s, d), weights);
+        Color::rgba(
+            channel(src.r, dst.r),
+            channel(src.g, dst.g),
+            channel(src.b, dst.b),
+            a,
+        )
+    }
+
+    // whether the source can be copied without reading the destination
+    pub(crate) fn is_copy(self, src: Color) -> bool {
+        match self {
+            BlendMode::Replace => true,
+            BlendMode::SourceOver => src.a == 255,
+            _ => false,
+        }
+    }
+}
+
+// a * b / 255, rounded
+fn mul(a: u8, b: u8) -> u8 {
+    div255(a as u32 * b as u32)
+}
+
+// from a towards b by t / 255
+fn lerp(a: u8, b: u8, t: u8) -> u8 {
+    let t = t as u32;
+    div255(a as u32 * (255 - t) + b as u32 * t)
+}
+
+// weighted average of a and b, rounded, zero without any weight
+fn mix(a: u8, b: u8, (wa, wb): (u32, u32)) -> u8 {
+    let total = wa + wb;
+    if total == 0 {
+        return 0;
+    }
+    ((a as u32 * wa + b as u32 * wb + total / 2) / total) as u8
+}
+
+// v / 255 rounded, for v up to 255 * 255
+fn div255(v: u32) -> u8 {
+    let v = v + 128;
+    ((v + (v >> 8)) >> 8) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: [BlendMode; 7] = [
+        BlendMode::Replace,
+        BlendMode::SourceOver,
+        BlendMode::Add,
+        BlendMode::Subtract,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Xor,
+    ];
+    const CLEAR: Color = Color::rgba(0, 0, 0, 0);
+
+    #[test]
+    fn source_over_weights_by_both_alphas() {
+        let half_red = Color::rgba(255, 0, 0, 128);
+        let over = |dst| BlendMode::SourceOver.blend(half_red, dst, 255);
+        assert_eq!(over(CLEAR), half_red);
+        assert_eq!(over(Color::rgb(0, 0, 255)), Color::rgb(128, 0, 127));
+        assert_eq!(
+            over(Color::rgba(0, 0, 255, 128)),
+            Color::rgba(170, 0, 85, 192)
+        );
+        assert_eq!(
+            BlendMode::SourceOver.blend(Color::rgb(0, 255, 0), half_red, 255),
+            Color::rgb(0, 255, 0)
+        );
+    }
+
+    #[test]
+    fn every_mode_leaves_the_source_on_transparent_pixels() {
+        let src = Color::rgba(200, 100, 50, 160);
+        for mode in MODES {
+            assert_eq!(mode.blend(src, CLEAR, 255), src, "{mode:?}");
+        }
+        assert_eq!(
+            BlendMode::Replace.blend(Color::rgb(255, 0, 0), CLEAR, 128),
+            Color::rgba(255, 0, 0, 128)
+        );
+    }
+
+    #[test]
+    fn modes_on_opaque_pixels() {
+        let (src, dst) = (Color::rgb(200, 100, 0), Color::rgb(100, 200, 255));
+        let blend = |mode: BlendMode| mode.blend(src, dst, 255);
+        assert_eq!(blend(BlendMode::Add), Color::rgb(255, 255, 255));
+        assert_eq!(blend(BlendMode::Subtract), Color::rgb(0, 100, 255));
+        assert_eq!(blend(BlendMode::Multiply), Color::rgb(78, 78, 0));
+        assert_eq!(blend(BlendMode::Screen), Color::rgb(222, 222, 255));
+        assert_eq!(blend(BlendMode::Xor), Color::rgb(172, 172, 255));
+        let twice = BlendMode::Xor.blend(src, blend(BlendMode::Xor), 255);
+        assert_eq!(twice, dst);
+        assert_eq!(
+            BlendMode::Add.blend(Color::rgba(100, 100, 100, 128), dst, 255),
+            Color::rgb(150, 250, 255)
+        );
+    }
+
+    #[test]
+    fn coverage_scales_the_source() {
+        let (src, dst) = (Color::rgb(255, 255, 255), Color::BLACK);
+        for mode in MODES {
+            assert_eq!(mode.blend(src, dst, 0), dst, "{mode:?}");
+        }
+        assert_eq!(
+            BlendMode::SourceOver.blend(src, dst, 51),
+            Color::rgb(51, 51, 51)
+        );
+        assert_eq!(
+            BlendMode::Replace.blend(Color::rgba(255, 255, 255, 0), dst, 255),
+            Color::rgba(255, 255, 255, 0)
+        );
+    }
+}
@@ -0,0 +1,487 @@
+use std::cell::Cell;
+
+use crate::{
+    blend::BlendMode,
+    color::Color,
+    format::PixelFormat,
+    gradient::{Paint, Shader},
+    palette::Palette,
+    rect::Rect,
+    transform::Transform,
+};
+
+// palette of indexed canvases created without one
+static GRAYSCALE: Palette = Palette::grayscale();
+
+/// A borrowed pixel buffer that can be drawn on.
+///
+/// Every write is clipped against the buffer edges, so shapes may extend past
+/// them. The region touched by drawing is tracked and can be read back with
+/// [`Canvas::dirty_rect`] to keep partial texture uploads cheap. Drawing
+/// combines colors with the buffer using the current [`BlendMode`], shapes
+/// get smooth edges when anti-aliasing is turned on.
+///
+/// Coordinates passed to drawing methods are local ones, mapped to canvas
+/// pixels by the current [`Transform`], and drawing is limited to the current
+/// clip rectangle. Both come from stacks that are pushed and popped around a
+/// group of draw calls.
+pub struct Canvas<'a> {
+    pixels: &'a mut [u8],
+    width: u32,
+    height: u32,
+    format: PixelFormat,
+    // colors of an Indexed8 buffer
+    palette: &'a Palette,
+    // last color looked up in the palette and its index, runs of one color
+    // are the common case and the search covers all 256 entries
+    nearest: Cell<Option<(Color, u8)>>,
+    blend_mode: BlendMode,
+    antialias: bool,
+    clips: Vec<Rect>,
+    transforms: Vec<Transform>,
+    // gradient of the fill in progress, it replaces the drawn color
+    shader: Option<Shader>,
+    dirty: Option<Rect>,
+}
+
+impl<'a> Canvas<'a> {
+    /// Wraps tightly packed RGBA rows of `width * height` pixels.
+    pub fn new(pixels: &'a mut [u8], width: u32, height: u32) -> Self {
+        Self::with_format(pixels, width, height, PixelFormat::default())
+    }
+
+    /// Wraps a buffer whose channel order is given by `format`. Indexed
+    /// buffers use the grayscale palette, see [`Canvas::with_palette`].
+    pub fn with_format(pixels: &'a mut [u8], width: u32, height: u32, format: PixelFormat) -> Self {
+        Self::with_format_and_palette(pixels, width, height, format, &GRAYSCALE)
+    }
+
+    /// Wraps a [`PixelFormat::Indexed8`] buffer of one palette index per
+    /// pixel.
+    pub fn with_palette(
+        pixels: &'a mut [u8],
+        width: u32,
+        height: u32,
+        palette: &'a Palette,
+    ) -> Self {
+        Self::with_format_and_palette(pixels, width, height, PixelFormat::Indexed8, palette)
+    }
+
+    pub(crate) fn with_format_and_palette(
+        pixels: &'a mut [u8],
+        width: u32,
+        height: u32,
+        format: PixelFormat,
+        palette: &'a Palette,
+    ) -> Self {
+        let len = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|len| len.checked_mul(format.bytes_per_pixel() as usize));
+        assert!(
+            len == Some(pixels.len()),
+            "pixel buffer doesn't match {width}x{height}, {}",
+            len.map_or("whose size overflows".into(), |len| format!(
+                "{len} bytes expected but got {}",
+                pixels.len()
+            ))
+        );
+        Self {
+            pixels,
+            width,
+            height,
+            format,
+            palette,
+            nearest: Cell::new(None),
+            blend_mode: BlendMode::default(),
+            antialias: false,
+            clips: Vec::new(),
+            transforms: Vec::new(),
+            shader: None,
+            dirty: None,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The whole canvas as a rectangle.
+    pub fn bounds(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        self.pixels
+    }
+
+    /// Raw access to the bytes, writes through here are not tracked as dirty.
+    pub fn pixels_mut(&mut self) -> &mut [u8] {
+        self.pixels
+    }
+
+    /// Bounding box of everything drawn so far, `None` if nothing was.
+    pub fn dirty_rect(&self) -> Option<Rect> {
+        self.dirty
+    }
+
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Blend mode used by every following draw call.
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
+    /// Runs `f` with `mode` as the blend mode and restores the previous one
+    /// afterwards, for a single draw call or a group of them.
+    pub fn with_blend_mode<R>(&mut self, mode: BlendMode, f: impl FnOnce(&mut Self) -> R) -> R {
+        let previous = std::mem::replace(&mut self.blend_mode, mode);
+        let result = f(self);
+        self.blend_mode = previous;
+        result
+    }
+
+    pub fn antialias(&self) -> bool {
+        self.antialias
+    }
+
+    /// Draws lines, ellipses and polygons with partially covered edge pixels
+    /// blended in, off by default.
+    pub fn set_antialias(&mut self, antialias: bool) {
+        self.antialias = antialias;
+    }
+
+    /// Runs `f` with anti-aliasing set to `antialias` and restores the
+    /// previous setting afterwards.
+    pub fn with_antialias<R>(&mut self, antialias: bool, f: impl FnOnce(&mut Self) -> R) -> R {
+        let previous = std::mem::replace(&mut self.antialias, antialias);
+        let result = f(self);
+        self.antialias = previous;
+        result
+    }
+
+    /// Local to canvas transform of the drawing methods.
+    pub fn transform(&self) -> Transform {
+        self.transforms.last().copied().unwrap_or_default()
+    }
+
+    /// Applies `transform` to local coordinates before the current transform,
+    /// until the matching [`Canvas::pop_transform`].
+    pub fn push_transform(&mut self, transform: Transform) {
+        let combined = transform.then(&self.transform());
+        self.transforms.push(combined);
+    }
+
+    pub fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+
+    /// Runs `f` with `transform` pushed and pops it afterwards.
+    pub fn with_transform<R>(&mut self, transform: Transform, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.push_transform(transform);
+        let result = f(self);
+        self.pop_transform();
+        result
+    }
+
+    /// Area drawing is limited to, in canvas pixels.
+    pub fn clip(&self) -> Rect {
+        self.clips.last().copied().unwrap_or(self.bounds())
+    }
+
+    /// Limits drawing to `rect` in local coordinates, on top of the current
+    /// clip, until the matching [`Canvas::pop_clip`]. Under a rotation the
+    /// bounding box of the rotated rectangle is used.
+    pub fn push_clip(&mut self, rect: Rect) {
+        let rect = self.transform().bounding_rect(rect);
+        let clip = self.clip();
+        let clip = clip
+            .intersect(&rect)
+            .unwrap_or(Rect::new(clip.x, clip.y, 0, 0));
+        self.clips.push(clip);
+    }
+
+    pub fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    /// Runs `f` with `rect` pushed as a clip and pops it afterwards.
+    pub fn with_clip<R>(&mut self, rect: Rect, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.push_clip(rect);
+        let result = f(self);
+        self.pop_clip();
+        result
+    }
+
+    /// Reads a pixel in canvas coordinates, not affected by the transform.
+    pub fn get_pixel(&self, x: i32, y: i32) -> Option<Color> {
+        let index = self.index(x, y)?;
+        Some(self.decode(index))
+    }
+
+    /// Blends a single pixel, which covers more than one canvas pixel when
+    /// the transform scales up.
+    pub fn set_pixel(&mut self, x: i32, y: i32, color: Color) {
+        match self.transform().pixel_offset() {
+            Some((dx, dy)) => {
+                // beyond the i32 range is outside any canvas
+                if let (Some(x), Some(y)) = (x.checked_add(dx), y.checked_add(dy)) {
+                    self.device_pixel(x, y, color);
+                }
+            }
+            None => self.fill_rect(Rect::new(x, y, 1, 1), color),
+        }
+    }
+
+    /// Fills the clip area, the whole canvas unless a clip was pushed, always
+    /// replacing what was there.
+    pub fn clear(&mut self, color: Color) {
+        let clip = self.clip();
+        self.with_blend_mode(BlendMode::Replace, |canvas| {
+            for y in clip.y..clip.bottom() {
+                canvas.write_span(y, clip.x, clip.right(), color);
+            }
+        });
+    }
+
+    // the public drawing methods take local coordinates and go through the
+    // transform, the device_ methods behind them work in clipped canvas pixels
+
+    pub(crate) fn device_pixel(&mut self, x: i32, y: i32, color: Color) {
+        if !self.clip().contains(x, y) {
+            return;
+        }
+        if let Some(index) = self.index(x, y) {
+            self.write(index, color);
+            self.touch(Rect::new(x, y, 1, 1));
+        }
+    }
+
+    // runs a fill with `paint`, the color given to `f` is the one to draw
+    // with and gets replaced pixel by pixel for gradients
+    pub(crate) fn with_paint(&mut self, paint: Paint, f: impl FnOnce(&mut Self, Color)) {
+        match paint {
+            Paint::Solid(color) => f(self, color),
+            Paint::Gradient(gradient) => {
+                let Some(shader) = Shader::new(gradient, &self.transform()) else {
+                    return;
+                };
+                let previous = self.shader.replace(shader);
+                f(self, Color::TRANSPARENT);
+                self.shader = previous;
+            }
+        }
+    }
+
+    // byte offset of a pixel, None outside the canvas
+    pub(crate) fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return None;
+        }
+        Some((y as usize * self.width as usize + x as usize) * self.bytes_per_pixel())
+    }
+
+    pub(crate) fn bytes_per_pixel(&self) -> usize {
+        self.format.bytes_per_pixel() as usize
+    }
+
+    // color in the byte order of the buffer, only the first
+    // `bytes_per_pixel` bytes are used
+    pub(crate) fn encode(&self, color: Color) -> [u8; 4] {
+        match self.format {
+            PixelFormat::Rgba8Srgb | PixelFormat::Rgba8Linear => {
+                [color.r, color.g, color.b, color.a]
+            }
+            PixelFormat::Bgra8Srgb | PixelFormat::Bgra8Linear => {
+                [color.b, color.g, color.r, color.a]
+            }
+            PixelFormat::Indexed8 => [self.nearest(color), 0, 0, 0],
+        }
+    }
+
+    fn nearest(&self, color: Color) -> u8 {
+        match self.nearest.get() {
+            Some((last, index)) if last == color => index,
+            _ => {
+                let index = self.palette.nearest(color);
+                self.nearest.set(Some((color, index)));
+                index
+            }
+        }
+    }
+
+    // color stored at a byte offset
+    pub(crate) fn decode(&self, index: usize) -> Color {
+        let p = &self.pixels[index..index + self.bytes_per_pixel()];
+        match self.format {
+            PixelFormat::Rgba8Srgb | PixelFormat::Rgba8Linear => {
+                Color::rgba(p[0], p[1], p[2], p[3])
+            }
+            PixelFormat::Bgra8Srgb | PixelFormat::Bgra8Linear => {
+                Color::rgba(p[2], p[1], p[0], p[3])
+            }
+            PixelFormat::Indexed8 => self.palette.get(p[0]),
+        }
+    }
+
+    pub(crate) fn write(&mut self, index: usize, color: Color) {
+        self.write_coverage(index, color, 255);
+    }
+
+    // blends into the pixel at a byte offset, coverage of 255 is fully inside
+    pub(crate) fn write_coverage(&mut self, index: usize, color: Color, coverage: u8) {
+        if coverage == 0 {
+            return;
+        }
+        let color = match &self.shader {
+            Some(shader) => {
+                let pixel = index / self.bytes_per_pixel();
+                let width = self.width as usize;
+                shader.color_at((pixel % width) as i32, (pixel / width) as i32)
+            }
+            None => color,
+        };
+        let color = if coverage == 255 && self.blend_mode.is_copy(color) {
+            color
+        } else {
+            self.blend_mode.blend(color, self.decode(index), coverage)
+        };
+        self.store(index, color);
+    }
+
+    // overwrites the pixel at a byte offset, no blending
+    pub(crate) fn store(&mut self, index: usize, color: Color) {
+        let bytes = self.encode(color);
+        let size = self.bytes_per_pixel();
+        self.pixels[index..index + size].copy_from_slice(&bytes[..size]);
+    }
+
+    // partially covered pixel, clipped
+    pub(crate) fn blend_pixel(&mut self, x: i32, y: i32, color: Color, coverage: u8) {
+        if coverage == 0 || !self.clip().contains(x, y) {
+            return;
+        }
+        if let Some(index) = self.index(x, y) {
+            self.write_coverage(index, color, coverage);
+            self.touch(Rect::new(x, y, 1, 1));
+        }
+    }
+
+    // fills x0..x1 of row y, the span has to be clipped already
+    pub(crate) fn write_span(&mut self, y: i32, x0: i32, x1: i32, color: Color) {
+        if x0 >= x1 {
+            return;
+        }
+        let size = self.bytes_per_pixel();
+        let start = (y as usize * self.width as usize + x0 as usize) * size;
+        let end = start + (x1 - x0) as usize * size;
+        if self.shader.is_none() && self.blend_mode.is_copy(color) {
+            let bytes = self.encode(color);
+            for pixel in self.pixels[start..end].chunks_exact_mut(size) {
+                pixel.copy_from_slice(&bytes[..size]);
+            }
+        } else {
+            for index in (start..end).step_by(size) {
+                self.write(index, color);
+            }
+        }
+        self.touch(Rect::new(x0, y, (x1 - x0) as u32, 1));
+    }
+
+    // grows the dirty region, the rect has to be clipped already
+    pub(crate) fn touch(&mut self, rect: Rect) {
+        self.dirty = Some(match self.dirty {
+            Some(dirty) => dirty.union(&rect),
+            None => rect,
+        });
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    const RED: Color = Color::rgb(255, 0, 0);
+
+    pub(crate) fn buffer(width: u32, height: u32, format: PixelFormat) -> Vec<u8> {
+        vec![0; (width * height * format.bytes_per_pixel()) as usize]
+    }
+
+    // pixels of a canvas equal to `color`, row by row
+    pub(crate) fn painted(canvas: &Canvas, color: Color) -> Vec<(i32, i32)> {
+        let mut pixels = Vec::new();
+        for y in 0..canvas.height() as i32 {
+            for x in 0..canvas.width() as i32 {
+                if canvas.get_pixel(x, y) == Some(color) {
+                    pixels.push((x, y));
+                }
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn rgba_stores_channels_in_order() {
+        let mut pixels = buffer(2, 1, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 2, 1);
+        canvas.set_pixel(1, 0, Color::rgb(1, 2, 3));
+        assert_eq!(canvas.get_pixel(1, 0), Some(Color::rgb(1, 2, 3)));
+        assert_eq!(pixels, [0, 0, 0, 0, 1, 2, 3, 255]);
+    }
+
+    #[test]
+    fn bgra_swaps_red_and_blue() {
+        let mut pixels = buffer(1, 1, PixelFormat::Bgra8Linear);
+        let mut canvas = Canvas::with_format(&mut pixels, 1, 1, PixelFormat::Bgra8Linear);
+        canvas.set_pixel(0, 0, Color::rgb(1, 2, 3));
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::rgb(1, 2, 3)));
+        assert_eq!(pixels, [3, 2, 1, 255]);
+    }
+
+    #[test]
+    #[should_panic(expected = "whose size overflows")]
+    fn buffer_sizes_beyond_the_address_space_are_rejected() {
+        Canvas::new(&mut [], u32::MAX, u32::MAX);
+    }
+
+    #[test]
+    fn drawing_is_clipped_to_the_canvas() {
+        let mut pixels = buffer(4, 4, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 4, 4);
+        canvas.fill_rect(Rect::new(-10, 2, 100, 100), RED);
+        canvas.set_pixel(-1, 0, RED);
+        canvas.set_pixel(4, 0, RED);
+        let expected: Vec<_> = (2..4).flat_map(|y| (0..4).map(move |x| (x, y))).collect();
+        assert_eq!(painted(&canvas, RED), expected);
+    }
+
+    #[test]
+    fn dirty_rect_covers_what_was_drawn() {
+        let mut pixels = buffer(8, 8, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 8, 8);
+        assert_eq!(canvas.dirty_rect(), None);
+        canvas.fill_rect(Rect::new(-2, -2, 20, 20), RED);
+        canvas.set_pixel(20, 20, RED);
+        assert_eq!(canvas.dirty_rect(), Some(canvas.bounds()));
+
+        let mut pixels = buffer(8, 8, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 8, 8);
+        canvas.set_pixel(1, 2, RED);
+        canvas.fill_rect(Rect::new(4, 5, 2, 1), RED);
+        assert_eq!(canvas.dirty_rect(), Some(Rect::new(1, 2, 5, 4)));
+    }
+
+    #[test]
+    fn drawing_outside_leaves_nothing_dirty() {
+        let mut pixels = buffer(8, 8, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 8, 8);
+        canvas.fill_rect(Rect::new(10, 10, 4, 4), RED);
+        canvas.with_clip(Rect::new(0, 0, 2, 2), |canvas| canvas.set_pixel(5, 5, RED));
+        assert_eq!(canvas.dirty_rect(), None);
+    }
+}
@@ -0,0 +1,487 @@
+use std::{fmt, str::FromStr};
+
+use crate::error::Error;
+
+/// RGBA color with 8 bit sRGB encoded channels.
+///
+/// Alpha is straight, not premultiplied. Colors can be built from HSV or HSL,
+/// packed integers, `#rrggbb[aa]` strings and CSS color names, the latter two
+/// also through [`str::parse`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::rgba(r, g, b, 255)
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// The same color with its alpha replaced.
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+
+    /// From `0xRRGGBBAA`.
+    pub const fn from_rgba_u32(value: u32) -> Self {
+        let [r, g, b, a] = value.to_be_bytes();
+        Self::rgba(r, g, b, a)
+    }
+
+    /// As `0xRRGGBBAA`.
+    pub const fn to_rgba_u32(self) -> u32 {
+        u32::from_be_bytes([self.r, self.g, self.b, self.a])
+    }
+
+    /// From `0xAARRGGBB`.
+    pub const fn from_argb_u32(value: u32) -> Self {
+        let [a, r, g, b] = value.to_be_bytes();
+        Self::rgba(r, g, b, a)
+    }
+
+    /// As `0xAARRGGBB`.
+    pub const fn to_argb_u32(self) -> u32 {
+        u32::from_be_bytes([self.a, self.r, self.g, self.b])
+    }
+
+    /// From `0xBBGGRRAA`.
+    pub const fn from_bgra_u32(value: u32) -> Self {
+        let [b, g, r, a] = value.to_be_bytes();
+        Self::rgba(r, g, b, a)
+    }
+
+    /// As `0xBBGGRRAA`.
+    pub const fn to_bgra_u32(self) -> u32 {
+        u32::from_be_bytes([self.b, self.g, self.r, self.a])
+    }
+
+    /// Opaque color from a hue in degrees, wrapped into 0..360, and a
+    /// saturation and value in 0..=1.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let (s, v) = (saturation.clamp(0.0, 1.0), value.clamp(0.0, 1.0));
+        let chroma = v * s;
+        from_hue(hue, chroma, v - chroma)
+    }
+
+    /// Hue in degrees in 0..360, saturation and value in 0..=1. Grays have a
+    /// hue of 0. Alpha is left out.
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (max, min) = self.extremes();
+        let chroma = max - min;
+        let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+        (self.hue(), saturation, max)
+    }
+
+    /// Opaque color from a hue in degrees, wrapped into 0..360, and a
+    /// saturation and lightness in 0..=1.
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        let (s, l) = (saturation.clamp(0.0, 1.0), lightness.clamp(0.0, 1.0));
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        from_hue(hue, chroma, l - chroma / 2.0)
+    }
+
+    /// Hue in degrees in 0..360, saturation and lightness in 0..=1. Grays
+    /// have a hue of 0. Alpha is left out.
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let (max, min) = self.extremes();
+        let chroma = max - min;
+        let lightness = (max + min) / 2.0;
+        let saturation = if chroma == 0.0 {
+            0.0
+        } else {
+            chroma / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        (self.hue(), saturation, lightness)
+    }
+
+    /// Parses `#rrggbb` or `#rrggbbaa`, the `#` is optional.
+    pub fn from_hex(hex: &str) -> Result<Self, Error> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        let invalid = || Error::InvalidColor(hex.to_string());
+        if !matches!(digits.len(), 6 | 8) || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        let value = u32::from_str_radix(digits, 16).map_err(|_| invalid())?;
+        Ok(if digits.len() == 6 {
+            Self::from_rgba_u32(value << 8 | 0xff)
+        } else {
+            Self::from_rgba_u32(value)
+        })
+    }
+
+    /// Formats as `#rrggbb`, or `#rrggbbaa` when not opaque.
+    pub fn to_hex(self) -> String {
+        if self.a == 255 {
+            format!("#{:06x}", self.to_rgba_u32() >> 8)
+        } else {
+            format!("#{:08x}", self.to_rgba_u32())
+        }
+    }
+
+    /// Looks up a CSS color name in [`NAMED_COLORS`], ignoring case.
+    pub fn named(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        NAMED_COLORS
+            .binary_search_by(|(entry, _)| (*entry).cmp(name.as_str()))
+            .ok()
+            .map(|index| NAMED_COLORS[index].1)
+    }
+
+    /// Mix of both colors channel by channel, `t` of 0 is `self` and 1 is
+    /// `other`.
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Color::rgba(
+            channel(self.r, other.r),
+            channel(self.g, other.g),
+            channel(self.b, other.b),
+            channel(self.a, other.a),
+        )
+    }
+
+    /// Like [`Color::lerp`] but weighting the color channels by alpha, so
+    /// fading towards a transparent color doesn't pick up its hidden color.
+    pub fn lerp_premultiplied(self, other: Color, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let (a0, a1) = (self.a as f32, other.a as f32);
+        let alpha = a0 + (a1 - a0) * t;
+        if alpha <= 0.0 {
+            return Color::TRANSPARENT;
+        }
+        let channel = |c0: u8, c1: u8| {
+            let premultiplied = c0 as f32 * a0 + (c1 as f32 * a1 - c0 as f32 * a0) * t;
+            (premultiplied / alpha).round().clamp(0.0, 255.0) as u8
+        };
+        Color::rgba(
+            channel(self.r, other.r),
+            channel(self.g, other.g),
+            channel(self.b, other.b),
+            alpha.round() as u8,
+        )
+    }
+
+    // largest and smallest channel in 0..=1
+    fn extremes(self) -> (f32, f32) {
+        let [r, g, b] = self.unit();
+        (r.max(g).max(b), r.min(g).min(b))
+    }
+
+    // hue shared by HSV and HSL, in degrees
+    fn hue(self) -> f32 {
+        let [r, g, b] = self.unit();
+        let (max, min) = self.extremes();
+        let chroma = max - min;
+        if chroma == 0.0 {
+            return 0.0;
+        }
+        let sector = if max == r {
+            ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / chroma + 2.0
+        } else {
+            (r - g) / chroma + 4.0
+        };
+        sector * 60.0
+    }
+
+    fn unit(self) -> [f32; 3] {
+        [self.r, self.g, self.b].map(|c| c as f32 / 255.0)
+    }
+}
+
+// color with the given hue and chroma, lifted by `lightest` on every channel
+fn from_hue(hue: f32, chroma: f32, lightest: f32) -> Color {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let channel = |c: f32| ((c + lightest) * 255.0).round().clamp(0.0, 255.0) as u8;
+    Color::rgb(channel(r), channel(g), channel(b))
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+/// Parses a hex color as in [`Color::from_hex`] or a CSS color name.
+impl FromStr for Color {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match Color::named(s) {
+            Some(color) => Ok(color),
+            None => Color::from_hex(s),
+        }
+    }
+}
+
+/// The CSS named colors, sorted by name.
+pub const NAMED_COLORS: &[(&str, Color)] = &[
+    ("aliceblue", Color::rgb(240, 248, 255)),
+    ("antiquewhite", Color::rgb(250, 235, 215)),
+    ("aqua", Color::rgb(0, 255, 255)),
+    ("aquamarine", Color::rgb(127, 255, 212)),
+    ("azure", Color::rgb(240, 255, 255)),
+    ("beige", Color::rgb(245, 245, 220)),
+    ("bisque", Color::rgb(255, 228, 196)),
+    ("black", Color::rgb(0, 0, 0)),
+    ("blanchedalmond", Color::rgb(255, 235, 205)),
+    ("blue", Color::rgb(0, 0, 255)),
+    ("blueviolet", Color::rgb(138, 43, 226)),
+    ("brown", Color::rgb(165, 42, 42)),
+    ("burlywood", Color::rgb(222, 184, 135)),
+    ("cadetblue", Color::rgb(95, 158, 160)),
+    ("chartreuse", Color::rgb(127, 255, 0)),
+    ("chocolate", Color::rgb(210, 105, 30)),
+    ("coral", Color::rgb(255, 127, 80)),
+    ("cornflowerblue", Color::rgb(100, 149, 237)),
+    ("cornsilk", Color::rgb(255, 248, 220)),
+    ("crimson", Color::rgb(220, 20, 60)),
+    ("cyan", Color::rgb(0, 255, 255)),
+    ("darkblue", Color::rgb(0, 0, 139)),
+    ("darkcyan", Color::rgb(0, 139, 139)),
+    ("darkgoldenrod", Color::rgb(184, 134, 11)),
+    ("darkgray", Color::rgb(169, 169, 169)),
+    ("darkgreen", Color::rgb(0, 100, 0)),
+    ("darkgrey", Color::rgb(169, 169, 169)),
+    ("darkkhaki", Color::rgb(189, 183, 107)),
+    ("darkmagenta", Color::rgb(139, 0, 139)),
+    ("darkolivegreen", Color::rgb(85, 107, 47)),
+    ("darkorange", Color::rgb(255, 140, 0)),
+    ("darkorchid", Color::rgb(153, 50, 204)),
+    ("darkred", Color::rgb(139, 0, 0)),
+    ("darksalmon", Color::rgb(233, 150, 122)),
+    ("darkseagreen", Color::rgb(143, 188, 143)),
+    ("darkslateblue", Color::rgb(72, 61, 139)),
+    ("darkslategray", Color::rgb(47, 79, 79)),
+    ("darkslategrey", Color::rgb(47, 79, 79)),
+    ("darkturquoise", Color::rgb(0, 206, 209)),
+    ("darkviolet", Color::rgb(148, 0, 211)),
+    ("deeppink", Color::rgb(255, 20, 147)),
+    ("deepskyblue", Color::rgb(0, 191, 255)),
+    ("dimgray", Color::rgb(105, 105, 105)),
+    ("dimgrey", Color::rgb(105, 105, 105)),
+    ("dodgerblue", Color::rgb(30, 144, 255)),
+    ("firebrick", Color::rgb(178, 34, 34)),
+    ("floralwhite", Color::rgb(255, 250, 240)),
+    ("forestgreen", Color::rgb(34, 139, 34)),
+    ("fuchsia", Color::rgb(255, 0, 255)),
+    ("gainsboro", Color::rgb(220, 220, 220)),
+    ("ghostwhite", Color::rgb(248, 248, 255)),
+    ("gold", Color::rgb(255, 215, 0)),
+    ("goldenrod", Color::rgb(218, 165, 32)),
+    ("gray", Color::rgb(128, 128, 128)),
+    ("green", Color::rgb(0, 128, 0)),
+    ("greenyellow", Color::rgb(173, 255, 47)),
+    ("grey", Color::rgb(128, 128, 128)),
+    ("honeydew", Color::rgb(240, 255, 240)),
+    ("hotpink", Color::rgb(255, 105, 180)),
+    ("indianred", Color::rgb(205, 92, 92)),
+    ("indigo", Color::rgb(75, 0, 130)),
+    ("ivory", Color::rgb(255, 255, 240)),
+    ("khaki", Color::rgb(240, 230, 140)),
+    ("lavender", Color::rgb(230, 230, 250)),
+    ("lavenderblush", Color::rgb(255, 240, 245)),
+    ("lawngreen", Color::rgb(124, 252, 0)),
+    ("lemonchiffon", Color::rgb(255, 250, 205)),
+    ("lightblue", Color::rgb(173, 216, 230)),
+    ("lightcoral", Color::rgb(240, 128, 128)),
+    ("lightcyan", Color::rgb(224, 255, 255)),
+    ("lightgoldenrodyellow", Color::rgb(250, 250, 210)),
+    ("lightgray", Color::rgb(211, 211, 211)),
+    ("lightgreen", Color::rgb(144, 238, 144)),
+    ("lightgrey", Color::rgb(211, 211, 211)),
+    ("lightpink", Color::rgb(255, 182, 193)),
+    ("lightsalmon", Color::rgb(255, 160, 122)),
+    ("lightseagreen", Color::rgb(32, 178, 170)),
+    ("lightskyblue", Color::rgb(135, 206, 250)),
+    ("lightslategray", Color::rgb(119, 136, 153)),
+    ("lightslategrey", Color::rgb(119, 136, 153)),
+    ("lightsteelblue", Color::rgb(176, 196, 222)),
+    ("lightyellow", Color::rgb(255, 255, 224)),
+    ("lime", Color::rgb(0, 255, 0)),
+    ("limegreen", Color::rgb(50, 205, 50)),
+    ("linen", Color::rgb(250, 240, 230)),
+    ("magenta", Color::rgb(255, 0, 255)),
+    ("maroon", Color::rgb(128, 0, 0)),
+    ("mediumaquamarine", Color::rgb(102, 205, 170)),
+    ("mediumblue", Color::rgb(0, 0, 205)),
+    ("mediumorchid", Color::rgb(186, 85, 211)),
+    ("mediumpurple", Color::rgb(147, 112, 219)),
+    ("mediumseagreen", Color::rgb(60, 179, 113)),
+    ("mediumslateblue", Color::rgb(123, 104, 238)),
+    ("mediumspringgreen", Color::rgb(0, 250, 154)),
+    ("mediumturquoise", Color::rgb(72, 209, 204)),
+    ("mediumvioletred", Color::rgb(199, 21, 133)),
+    ("midnightblue", Color::rgb(25, 25, 112)),
+    ("mintcream", Color::rgb(245, 255, 250)),
+    ("mistyrose", Color::rgb(255, 228, 225)),
+    ("moccasin", Color::rgb(255, 228, 181)),
+    ("navajowhite", Color::rgb(255, 222, 173)),
+    ("navy", Color::rgb(0, 0, 128)),
+    ("oldlace", Color::rgb(253, 245, 230)),
+    ("olive", Color::rgb(128, 128, 0)),
+    ("olivedrab", Color::rgb(107, 142, 35)),
+    ("orange", Color::rgb(255, 165, 0)),
+    ("orangered", Color::rgb(255, 69, 0)),
+    ("orchid", Color::rgb(218, 112, 214)),
+    ("palegoldenrod", Color::rgb(238, 232, 170)),
+    ("palegreen", Color::rgb(152, 251, 152)),
+    ("paleturquoise", Color::rgb(175, 238, 238)),
+    ("palevioletred", Color::rgb(219, 112, 147)),
+    ("papayawhip", Color::rgb(255, 239, 213)),
+    ("peachpuff", Color::rgb(255, 218, 185)),
+    ("peru", Color::rgb(205, 133, 63)),
+    ("pink", Color::rgb(255, 192, 203)),
+    ("plum", Color::rgb(221, 160, 221)),
+    ("powderblue", Color::rgb(176, 224, 230)),
+    ("purple", Color::rgb(128, 0, 128)),
+    ("rebeccapurple", Color::rgb(102, 51, 153)),
+    ("red", Color::rgb(255, 0, 0)),
+    ("rosybrown", Color::rgb(188, 143, 143)),
+    ("royalblue", Color::rgb(65, 105, 225)),
+    ("saddlebrown", Color::rgb(139, 69, 19)),
+    ("salmon", Color::rgb(250, 128, 114)),
+    ("sandybrown", Color::rgb(244, 164, 96)),
+    ("seagreen", Color::rgb(46, 139, 87)),
+    ("seashell", Color::rgb(255, 245, 238)),
+    ("sienna", Color::rgb(160, 82, 45)),
+    ("silver", Color::rgb(192, 192, 192)),
+    ("skyblue", Color::rgb(135, 206, 235)),
+    ("slateblue", Color::rgb(106, 90, 205)),
+    ("slategray", Color::rgb(112, 128, 144)),
+    ("slategrey", Color::rgb(112, 128, 144)),
+    ("snow", Color::rgb(255, 250, 250)),
+    ("springgreen", Color::rgb(0, 255, 127)),
+    ("steelblue", Color::rgb(70, 130, 180)),
+    ("tan", Color::rgb(210, 180, 140)),
+    ("teal", Color::rgb(0, 128, 128)),
+    ("thistle", Color::rgb(216, 191, 216)),
+    ("tomato", Color::rgb(255, 99, 71)),
+    ("transparent", Color::rgba(0, 0, 0, 0)),
+    ("turquoise", Color::rgb(64, 224, 208)),
+    ("violet", Color::rgb(238, 130, 238)),
+    ("wheat", Color::rgb(245, 222, 179)),
+    ("white", Color::rgb(255, 255, 255)),
+    ("whitesmoke", Color::rgb(245, 245, 245)),
+    ("yellow", Color::rgb(255, 255, 0)),
+    ("yellowgreen", Color::rgb(154, 205, 50)),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // every color with channels on a grid of 18 levels
+    fn grid() -> impl Iterator<Item = Color> {
+        let levels = (0..=255).step_by(15);
+        levels.clone().flat_map(move |r| {
+            let levels = levels.clone();
+            levels
+                .clone()
+                .flat_map(move |g| levels.clone().map(move |b| Color::rgb(r, g, b)))
+        })
+    }
+
+    #[test]
+    fn hsv_and_hsl_round_trip() {
+        for color in grid() {
+            let (h, s, v) = color.to_hsv();
+            assert_eq!(Color::from_hsv(h, s, v), color, "hsv {h} {s} {v}");
+            let (h, s, l) = color.to_hsl();
+            assert_eq!(Color::from_hsl(h, s, l), color, "hsl {h} {s} {l}");
+            assert!((0.0..360.0).contains(&h));
+        }
+        assert_eq!(Color::from_hsv(-120.0, 1.0, 1.0), Color::rgb(0, 0, 255));
+        assert_eq!(Color::from_hsl(480.0, 1.0, 0.5), Color::rgb(0, 255, 0));
+        assert_eq!(Color::rgb(128, 128, 128).to_hsv().0, 0.0);
+    }
+
+    #[test]
+    fn hex_parsing_and_formatting() {
+        assert_eq!(
+            Color::from_hex("#ff8000").ok(),
+            Some(Color::rgb(255, 128, 0))
+        );
+        assert_eq!(
+            Color::from_hex("FF800080").ok(),
+            Some(Color::rgba(255, 128, 0, 128))
+        );
+        for invalid in [
+            "", "#", "#fff", "#ff80001", "#gg0000", "#+f8000", " #ff8000",
+        ] {
+            assert!(Color::from_hex(invalid).is_err(), "{invalid:?}");
+        }
+        assert_eq!(Color::rgb(255, 128, 0).to_hex(), "#ff8000");
+        assert_eq!(Color::rgba(1, 2, 3, 4).to_string(), "#01020304");
+        assert_eq!(" Tomato ".parse().ok(), Some(Color::rgb(255, 99, 71)));
+        assert_eq!("#01020304".parse().ok(), Some(Color::rgba(1, 2, 3, 4)));
+    }
+
+    #[test]
+    fn named_colors_are_sorted_for_lookup() {
+        // binary search in `Color::named` depends on this
+        for pair in NAMED_COLORS.windows(2) {
+            assert!(pair[0].0 < pair[1].0, "{} before {}", pair[0].0, pair[1].0);
+        }
+        for &(name, color) in NAMED_COLORS {
+            assert_eq!(name, name.to_ascii_lowercase());
+            assert_eq!(Color::named(name), Some(color));
+        }
+        assert_eq!(
+            Color::named("RebeccaPurple"),
+            Some(Color::rgb(102, 51, 153))
+        );
+        assert_eq!(Color::named("notacolor"), None);
+    }
+
+    #[test]
+    fn packed_u32_orders() {
+        let color = Color::rgba(1, 2, 3, 4);
+        assert_eq!(color.to_rgba_u32(), 0x01020304);
+        assert_eq!(color.to_argb_u32(), 0x04010203);
+        assert_eq!(color.to_bgra_u32(), 0x03020104);
+        assert_eq!(Color::from_rgba_u32(0x01020304), color);
+        assert_eq!(Color::from_argb_u32(0x04010203), color);
+        assert_eq!(Color::from_bgra_u32(0x03020104), color);
+    }
+
+    #[test]
+    fn lerp_between_colors() {
+        let (from, to) = (Color::rgba(0, 100, 200, 0), Color::rgba(255, 200, 0, 255));
+        assert_eq!(from.lerp(to, 0.0), from);
+        assert_eq!(from.lerp(to, 1.0), to);
+        assert_eq!(from.lerp(to, 2.0), to);
+        assert_eq!(from.lerp(to, 0.5), Color::rgba(128, 150, 100, 128));
+        // the hidden color of a transparent end doesn't show
+        assert_eq!(
+            Color::rgb(255, 0, 0).lerp_premultiplied(Color::TRANSPARENT, 0.5),
+            Color::rgba(255, 0, 0, 128)
+        );
+        assert_eq!(
+            Color::TRANSPARENT.lerp_premultiplied(Color::TRANSPARENT, 0.5),
+            Color::TRANSPARENT
+        );
+    }
+}
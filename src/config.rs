@@ -0,0 +1,152 @@
+use crate::{format::PixelFormat, palette::Palette, scaling::ScalingMode};
+use winit::dpi::LogicalSize;
+
+const INITIAL_WINDOW: (u32, u32) = (640, 480); // Default size
+
+/// Settings used by [`crate::init::run_with`] to create the window and renderer.
+///
+/// ```no_run
+/// use w_pixbuf::{config::Config, scaling::ScalingMode};
+///
+/// let config = Config::new()
+///     .with_title("demo")
+///     .with_window_size(800, 600)
+///     .with_buffer_size(320, 240)
+///     .with_scaling(ScalingMode::PixelPerfect)
+///     .with_vsync(true);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Window title, followed by the FPS readout when `show_fps` is set.
+    pub title: String,
+    /// Initial inner size of the window.
+    pub window_size: LogicalSize<u32>,
+    /// Size of the pixel buffer, defaults to the window size.
+    pub buffer_size: Option<LogicalSize<u32>>,
+    /// Layout of the bytes in the pixel buffer.
+    pub pixel_format: PixelFormat,
+    /// Initial colors of a [`PixelFormat::Indexed8`] buffer.
+    pub palette: Palette,
+    /// How the pixel buffer is placed on the window when their sizes differ.
+    pub scaling: ScalingMode,
+    /// Reallocate the pixel buffer to the window size whenever it resizes.
+    pub buffer_follows_window: bool,
+    /// Keep the overlapping content when the pixel buffer is reallocated.
+    pub preserve_buffer: bool,
+    /// Only upload the regions passed to `State::mark_dirty` instead of the
+    /// whole pixel buffer every frame.
+    pub dirty_tracking: bool,
+    /// How frames are synced to the display. Modes the surface doesn't
+    /// support fall back to `Fifo`, which is always available.
+    pub present_mode: wgpu::PresentMode,
+    pub resizable: bool,
+    /// Start in borderless fullscreen on the current monitor.
+    pub fullscreen: bool,
+    /// Append FPS and frame time to the window title every frame.
+    pub show_fps: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            title: String::from("w-pixbuf"),
+            window_size: LogicalSize::new(INITIAL_WINDOW.0, INITIAL_WINDOW.1),
+            buffer_size: None,
+            pixel_format: PixelFormat::default(),
+            palette: Palette::default(),
+            scaling: ScalingMode::default(),
+            buffer_follows_window: false,
+            preserve_buffer: true,
+            dirty_tracking: false,
+            present_mode: wgpu::PresentMode::AutoVsync,
+            resizable: true,
+            fullscreen: false,
+            show_fps: true,
+        }
+    }
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn with_window_size(mut self, width: u32, height: u32) -> Self {
+        self.window_size = LogicalSize::new(width, height);
+        self
+    }
+
+    pub fn with_buffer_size(mut self, width: u32, height: u32) -> Self {
+        self.buffer_size = Some(LogicalSize::new(width, height));
+        self
+    }
+
+    pub fn with_pixel_format(mut self, pixel_format: PixelFormat) -> Self {
+        self.pixel_format = pixel_format;
+        self
+    }
+
+    pub fn with_palette(mut self, palette: Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    pub fn with_scaling(mut self, scaling: ScalingMode) -> Self {
+        self.scaling = scaling;
+        self
+    }
+
+    pub fn with_buffer_follows_window(mut self, follows: bool) -> Self {
+        self.buffer_follows_window = follows;
+        self
+    }
+
+    pub fn with_preserve_buffer(mut self, preserve: bool) -> Self {
+        self.preserve_buffer = preserve;
+        self
+    }
+
+    pub fn with_dirty_tracking(mut self, dirty_tracking: bool) -> Self {
+        self.dirty_tracking = dirty_tracking;
+        self
+    }
+
+    pub fn with_present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    /// Shorthand for picking `AutoVsync` or `AutoNoVsync`.
+    pub fn with_vsync(self, vsync: bool) -> Self {
+        self.with_present_mode(if vsync {
+            wgpu::PresentMode::AutoVsync
+        } else {
+            wgpu::PresentMode::AutoNoVsync
+        })
+    }
+
+    pub fn with_resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    pub fn with_fullscreen(mut self, fullscreen: bool) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    pub fn with_show_fps(mut self, show_fps: bool) -> Self {
+        self.show_fps = show_fps;
+        self
+    }
+
+    /// Size of the pixel buffer after falling back to the window size.
+    pub fn buffer_size(&self) -> LogicalSize<u32> {
+        self.buffer_size.unwrap_or(self.window_size)
+    }
+}
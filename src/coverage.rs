@@ -0,0 +1,142 @@
+use crate::{canvas::Canvas, color::Color};
+
+// sub-scanlines per pixel row for anti-aliased fills
+const SAMPLES: usize = 8;
+
+// heights of the sub-scanlines inside row y
+pub(crate) fn sample_rows(y: i32) -> impl Iterator<Item = f32> {
+    (0..SAMPLES).map(move |k| y as f32 + (k as f32 + 0.5) / SAMPLES as f32)
+}
+
+// converts a 0..=1 fraction into a blend coverage
+pub(crate) fn to_coverage(fraction: f32) -> u8 {
+    (fraction.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+// area of each pixel in one row covered by a shape, built up from spans on
+// the sub-scanlines with exact horizontal coverage
+pub(crate) struct CoverageRow {
+    left: i32,
+    cells: Vec<f32>,
+}
+
+impl CoverageRow {
+    // cells for the pixels left..right
+    pub(crate) fn new(left: i32, right: i32) -> Self {
+        Self {
+            left,
+            cells: vec![0.0; (right - left).max(0) as usize],
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.cells.fill(0.0);
+    }
+
+    // adds the span [a, b) of one sub-scanline
+    pub(crate) fn add_span(&mut self, a: f32, b: f32) {
+        let weight = 1.0 / SAMPLES as f32;
+        let right = self.left + self.cells.len() as i32;
+        let (a, b) = (a.max(self.left as f32), b.min(right as f32));
+        if a >= b {
+            return;
+        }
+        let (first, last) = (a.floor() as i32, b.floor() as i32);
+        let cell = |x: i32| (x - self.left) as usize;
+        if first == last {
+            self.cells[cell(first)] += (b - a) * weight;
+            return;
+        }
+        self.cells[cell(first)] += (first as f32 + 1.0 - a) * weight;
+        for x in first + 1..last {
+            self.cells[cell(x)] += weight;
+        }
+        // b can sit exactly on the right end
+        if last < right {
+            self.cells[cell(last)] += (b - last as f32) * weight;
+        }
+    }
+}
+
+impl Canvas<'_> {
+    // blends a row of coverage, the row has to be clipped already
+    pub(crate) fn blend_row(&mut self, y: i32, row: &CoverageRow, color: Color) {
+        for (i, &fraction) in row.cells.iter().enumerate() {
+            self.blend_pixel(row.left + i as i32, y, color, to_coverage(fraction));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{canvas::tests::buffer, format::PixelFormat};
+
+    const WHITE: Color = Color::rgb(255, 255, 255);
+
+    fn cells(row: &CoverageRow) -> Vec<u8> {
+        row.cells
+            .iter()
+            .map(|&fraction| to_coverage(fraction))
+            .collect()
+    }
+
+    // red channel of every pixel, row by row, after drawing white on black
+    fn coverage(width: u32, height: u32, draw: impl FnOnce(&mut Canvas)) -> Vec<u8> {
+        let mut pixels = buffer(width, height, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, width, height);
+        canvas.clear(Color::BLACK);
+        canvas.set_antialias(true);
+        draw(&mut canvas);
+        pixels.chunks(4).map(|pixel| pixel[0]).collect()
+    }
+
+    #[test]
+    fn rows_add_up_exact_horizontal_coverage() {
+        let mut row = CoverageRow::new(2, 6);
+        for _ in sample_rows(0) {
+            row.add_span(2.5, 4.25);
+        }
+        assert_eq!(cells(&row), [128, 255, 64, 0]);
+        row.clear();
+        row.add_span(-10.0, 3.5);
+        row.add_span(5.0, 100.0);
+        assert_eq!(cells(&row), [32, 16, 0, 32]);
+        row.add_span(3.5, 4.0);
+        assert_eq!(cells(&row), [32, 32, 0, 32]);
+    }
+
+    #[test]
+    fn sample_rows_are_spread_through_the_pixel() {
+        let rows: Vec<_> = sample_rows(3).collect();
+        assert_eq!(rows.len(), SAMPLES);
+        assert!(rows.iter().all(|&y| y > 3.0 && y < 4.0));
+        assert_eq!(rows.iter().sum::<f32>() / SAMPLES as f32, 3.5);
+    }
+
+    #[test]
+    fn wu_lines_split_coverage_between_neighbors() {
+        let pixels = coverage(5, 2, |canvas| canvas.line(0, 0, 4, 1, WHITE));
+        // every column adds up to a full pixel
+        for x in 0..5 {
+            let column = pixels[x] as u32 + pixels[5 + x] as u32;
+            assert!(column.abs_diff(255) <= 1, "column {x}: {column}");
+        }
+        assert_eq!((pixels[0], pixels[5]), (255, 0));
+        assert_eq!((pixels[4], pixels[9]), (0, 255));
+        assert!(pixels[2].abs_diff(128) <= 1);
+    }
+
+    #[test]
+    fn circle_edges_are_partly_covered() {
+        let pixels = coverage(9, 9, |canvas| canvas.fill_circle(4, 4, 3, WHITE));
+        assert_eq!(pixels[4 * 9 + 4], 255);
+        assert_eq!(pixels[0], 0);
+        let partial = pixels.iter().filter(|&&c| c > 0 && c < 255).count();
+        assert!(partial > 0);
+        // the covered area is close to that of the circle
+        let area: f32 = pixels.iter().map(|&c| c as f32 / 255.0).sum();
+        let expected = std::f32::consts::PI * 3.5 * 3.5;
+        assert!((area - expected).abs() < 2.0, "{area} vs {expected}");
+    }
+}
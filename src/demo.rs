@@ -1,7 +1,4 @@
-use crate::{
-    draw::{draw_circle, draw_square, draw_triangle},
-    init::State,
-};
+use crate::{app::App, color::Color, init::State, rect::Rect, transform::Transform};
 use winit::{
     event::*,
     keyboard::{KeyCode, PhysicalKey},
@@ -13,37 +10,21 @@ pub enum Shape {
     SQUARE,
 }
 
-pub struct Color(u32);
-impl Color {
-    pub fn new(r: u8, g: u8, b: u8) -> Self {
-        Color(((r as u32) << 16) | ((g as u32) << 8) | (b as u32))
-    }
-
-    pub fn get_components(&self) -> (u8, u8, u8) {
-        let r = ((self.0 >> 16) & 0xFF) as u8;
-        let g = ((self.0 >> 8) & 0xFF) as u8;
-        let b = (self.0 & 0xFF) as u8;
-        (r, g, b)
-    }
-
-    pub fn cycle(&mut self, amount: i32) {
-        let (r, g, b) = self.get_components();
-        let new_r = ((r as i32 + amount) % 256).abs() as u8;
-        let new_g = ((g as i32 + amount / 2) % 256).abs() as u8;
-        let new_b = ((b as i32 + amount / 3) % 256).abs() as u8;
-        self.0 = ((new_r as u32) << 16) | ((new_g as u32) << 8) | (new_b as u32);
-    }
-}
-
 pub struct DemoApp {
     pub shape: Shape,
     pub color: Color,
 }
 
+impl Default for DemoApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DemoApp {
     pub fn new() -> Self {
         Self {
-            color: Color::new(255, 255, 255),
+            color: Color::rgb(100, 150, 200),
             shape: Shape::CIRCLE,
         }
     }
@@ -59,63 +40,90 @@ impl DemoApp {
         self.shape = Shape::SQUARE;
     }
     pub fn up(&mut self) {
-        self.color.cycle(25);
+        self.cycle(25.0);
     }
 
     pub fn down(&mut self) {
-        self.color.cycle(-25);
+        self.cycle(-25.0);
+    }
+
+    // turns the hue of the shape color by `degrees`
+    fn cycle(&mut self, degrees: f32) {
+        let (hue, saturation, value) = self.color.to_hsv();
+        self.color = Color::from_hsv(hue + degrees, saturation, value);
     }
 }
 
-pub fn demo_input_handler(state: &mut State<DemoApp>, event: &WindowEvent) -> bool {
-    match event {
-        WindowEvent::KeyboardInput {
-            event:
-                KeyEvent {
-                    state: ElementState::Pressed,
-                    physical_key: keycode,
-                    ..
-                },
-            ..
-        } => {
-            match keycode {
-                PhysicalKey::Code(KeyCode::KeyC) => {
-                    state.game_context.c();
-                    true
-                }
-                PhysicalKey::Code(KeyCode::KeyS) => {
-                    state.game_context.s();
-                    true
-                }
-                PhysicalKey::Code(KeyCode::KeyT) => {
-                    state.game_context.t();
-                    true
+impl App for DemoApp {
+    fn update(&mut self, state: &mut State, _dt: f64) {
+        let color = self.color;
+        let fps = state.fps();
+
+        state.draw(|canvas| {
+            canvas.clear(Color::BLACK);
+
+            // shapes are drawn around the origin, moved to the middle
+            let center =
+                Transform::translate((canvas.width() / 2) as f32, (canvas.height() / 2) as f32);
+            let size = canvas.width().min(canvas.height()) as f32 / 4.0;
+
+            canvas.with_transform(center, |canvas| match self.shape {
+                Shape::CIRCLE => {
+                    canvas.fill_circle(0, 0, size as u32, color);
                 }
-                PhysicalKey::Code(KeyCode::ArrowUp) => {
-                    state.game_context.up();
-                    true
+                Shape::SQUARE => {
+                    let size = size as i32;
+                    canvas.fill_rect(Rect::from_corners(-size, -size, size, size), color);
                 }
-                PhysicalKey::Code(KeyCode::ArrowDown) => {
-                    state.game_context.down();
-                    true
+                Shape::TRIANGLE => {
+                    let height_triangle = size * (3.0f32).sqrt();
+                    let v0 = (-size, height_triangle / 2.0); // bl
+                    let v1 = (size, height_triangle / 2.0); // br
+                    let v2 = (0.0, -height_triangle / 2.0); // t
+                    canvas.fill_triangle(v0, v1, v2, color);
                 }
-                _ => false, // Other key presses
-            }
-        }
-        _ => false,
+            });
+
+            canvas.draw_text(4, 4, &format!("FPS: {fps:.1}"), Color::WHITE, 1);
+        });
     }
-}
 
-pub fn demo_update_handler(state: &mut State<DemoApp>) {
-    match state.game_context.shape {
-        Shape::CIRCLE => {
-            draw_circle(state);
-        }
-        Shape::SQUARE => {
-            draw_square(state);
-        }
-        Shape::TRIANGLE => {
-            draw_triangle(state);
+    fn input(&mut self, _state: &mut State, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        state: ElementState::Pressed,
+                        physical_key: keycode,
+                        ..
+                    },
+                ..
+            } => {
+                match keycode {
+                    PhysicalKey::Code(KeyCode::KeyC) => {
+                        self.c();
+                        true
+                    }
+                    PhysicalKey::Code(KeyCode::KeyS) => {
+                        self.s();
+                        true
+                    }
+                    PhysicalKey::Code(KeyCode::KeyT) => {
+                        self.t();
+                        true
+                    }
+                    PhysicalKey::Code(KeyCode::ArrowUp) => {
+                        self.up();
+                        true
+                    }
+                    PhysicalKey::Code(KeyCode::ArrowDown) => {
+                        self.down();
+                        true
+                    }
+                    _ => false, // Other key presses
+                }
+            }
+            _ => false,
         }
     }
 }
@@ -0,0 +1,248 @@
+use crate::{canvas::Canvas, color::Color, rect::Rect};
+
+/// How colors between the palette entries are approximated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dither {
+    /// Every pixel takes the nearest palette color, giving flat bands.
+    #[default]
+    None,
+    /// Ordered dithering with a 2x2 Bayer matrix, a coarse regular pattern.
+    Bayer2,
+    /// Ordered dithering with a 4x4 Bayer matrix.
+    Bayer4,
+    /// Ordered dithering with an 8x8 Bayer matrix, the finest pattern.
+    Bayer8,
+    /// Error diffusion spreading the whole error to four neighbors.
+    FloydSteinberg,
+    /// Error diffusion as on early Macs, only three quarters of the error is
+    /// spread so the result keeps more contrast.
+    Atkinson,
+}
+
+// offsets and weights an error is passed on with, to pixels not visited yet
+const FLOYD_STEINBERG: &[(i32, i32, f32)] = &[
+    (1, 0, 7.0 / 16.0),
+    (-1, 1, 3.0 / 16.0),
+    (0, 1, 5.0 / 16.0),
+    (1, 1, 1.0 / 16.0),
+];
+const ATKINSON: &[(i32, i32, f32)] = &[
+    (1, 0, 1.0 / 8.0),
+    (2, 0, 1.0 / 8.0),
+    (-1, 1, 1.0 / 8.0),
+    (0, 1, 1.0 / 8.0),
+    (1, 1, 1.0 / 8.0),
+    (0, 2, 1.0 / 8.0),
+];
+
+impl Canvas<'_> {
+    /// Reduces the clip area, the whole canvas unless a clip was pushed, to
+    /// the colors of `palette`. Only the color channels are replaced, every
+    /// pixel keeps its alpha. Does nothing for an empty palette.
+    pub fn dither(&mut self, palette: &[Color], method: Dither) {
+        self.device_dither(self.clip(), palette, method);
+    }
+
+    /// Like [`Canvas::dither`], limited to `rect` in local coordinates.
+    pub fn dither_rect(&mut self, rect: Rect, palette: &[Color], method: Dither) {
+        let rect = self.transform().bounding_rect(rect);
+        self.device_dither(rect, palette, method);
+    }
+
+    fn device_dither(&mut self, rect: Rect, palette: &[Color], method: Dither) {
+        let Some(rect) = self.clip().intersect(&rect) else {
+            return;
+        };
+        if palette.is_empty() {
+            return;
+        }
+        match method {
+            Dither::None => self.ordered_dither(rect, palette, 0),
+            Dither::Bayer2 => self.ordered_dither(rect, palette, 1),
+            Dither::Bayer4 => self.ordered_dither(rect, palette, 2),
+            Dither::Bayer8 => self.ordered_dither(rect, palette, 3),
+            Dither::FloydSteinberg => self.diffuse(rect, palette, FLOYD_STEINBERG),
+            Dither::Atkinson => self.diffuse(rect, palette, ATKINSON),
+        }
+        self.touch(rect);
+    }
+
+    // offsets every pixel by its threshold in a 2^levels square Bayer matrix
+    // before picking the nearest color, zero levels is no dithering at all
+    fn ordered_dither(&mut self, rect: Rect, palette: &[Color], levels: u32) {
+        let cells = (1u32 << (2 * levels)) as f32;
+        let spread = palette_spacing(palette);
+        for y in rect.y..rect.bottom() {
+            for x in rect.x..rect.right() {
+                let Some(index) = self.index(x, y) else {
+                    continue;
+                };
+                // thresholds centered on zero, in -0.5..0.5
+                let threshold = (bayer(x as u32, y as u32, levels) as f32 + 0.5) / cells - 0.5;
+                let offset = threshold * spread;
+                let pixel = self.decode(index);
+                let target = [
+                    pixel.r as f32 + offset,
+                    pixel.g as f32 + offset,
+                    pixel.b as f32 + offset,
+                ];
+                self.put_nearest(index, pixel.a, target, palette);
+            }
+        }
+    }
+
+    // error diffusion, the difference to the chosen color is passed on to
+    // neighbors by `kernel` and never leaves the rect
+    fn diffuse(&mut self, rect: Rect, palette: &[Color], kernel: &[(i32, i32, f32)]) {
+        let width = rect.width as usize;
+        let mut errors = vec![[0.0f32; 3]; width * rect.height as usize];
+        for y in rect.y..rect.bottom() {
+            for x in rect.x..rect.right() {
+                let Some(index) = self.index(x, y) else {
+                    continue;
+                };
+                let cell = (y - rect.y) as usize * width + (x - rect.x) as usize;
+                let pixel = self.decode(index);
+                let error = errors[cell];
+                let target = [
+                    pixel.r as f32 + error[0],
+                    pixel.g as f32 + error[1],
+                    pixel.b as f32 + error[2],
+                ];
+                let chosen = self.put_nearest(index, pixel.a, target, palette);
+                let difference = [
+                    target[0] - chosen.r as f32,
+                    target[1] - chosen.g as f32,
+                    target[2] - chosen.b as f32,
+                ];
+                for &(dx, dy, weight) in kernel {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if !rect.contains(nx, ny) {
+                        continue;
+                    }
+                    let cell = (ny - rect.y) as usize * width + (nx - rect.x) as usize;
+                    for (error, difference) in errors[cell].iter_mut().zip(difference) {
+                        *error += difference * weight;
+                    }
+                }
+            }
+        }
+    }
+
+    // stores the palette color closest to `target` with the given alpha,
+    // returns the color picked
+    fn put_nearest(
+        &mut self,
+        index: usize,
+        alpha: u8,
+        target: [f32; 3],
+        palette: &[Color],
+    ) -> Color {
+        let nearest = nearest(palette, target);
+        self.store(index, Color::rgba(nearest.r, nearest.g, nearest.b, alpha));
+        nearest
+    }
+}
+
+// palette entry with the smallest squared distance to `target`
+fn nearest(palette: &[Color], target: [f32; 3]) -> Color {
+    let distance = |color: &Color| {
+        let dr = color.r as f32 - target[0];
+        let dg = color.g as f32 - target[1];
+        let db = color.b as f32 - target[2];
+        dr * dr + dg * dg + db * db
+    };
+    *palette
+        .iter()
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+        .expect("palette is not empty")
+}
+
+// rough distance between neighboring palette colors, as if the palette were
+// an even grid in the RGB cube, which scales the ordered dither pattern
+fn palette_spacing(palette: &[Color]) -> f32 {
+    let steps = (palette.len() as f32).cbrt() - 1.0;
+    255.0 / steps.max(1.0)
+}
+
+// entry of the 2^levels square Bayer matrix at x, y, built up from the 2x2
+// one with the lowest bits weighing the most
+fn bayer(x: u32, y: u32, levels: u32) -> u32 {
+    const BASE: [[u32; 2]; 2] = [[0, 2], [3, 1]];
+    (0..levels).fold(0, |value, bit| {
+        value * 4 + BASE[((y >> bit) & 1) as usize][((x >> bit) & 1) as usize]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{blend::BlendMode, canvas::tests::buffer, format::PixelFormat};
+
+    const WHITE: Color = Color::rgb(255, 255, 255);
+    const MONO: [Color; 2] = [Color::BLACK, WHITE];
+
+    // white pixels after dithering an 8x8 canvas of `color` to black and white
+    fn whites(color: Color, method: Dither) -> usize {
+        let mut pixels = buffer(8, 8, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 8, 8);
+        canvas.clear(color);
+        canvas.dither(&MONO, method);
+        pixels
+            .chunks(4)
+            .inspect(|pixel| assert!(pixel[0] == 0 || pixel[0] == 255))
+            .filter(|pixel| pixel[0] == 255)
+            .count()
+    }
+
+    #[test]
+    fn bayer_matrices_hold_every_threshold_once() {
+        let matrix = |levels| -> Vec<u32> {
+            let size = 1u32 << levels;
+            (0..size)
+                .flat_map(|y| (0..size).map(move |x| bayer(x, y, levels)))
+                .collect()
+        };
+        assert_eq!(matrix(1), [0, 2, 3, 1]);
+        assert_eq!(
+            matrix(2),
+            [0, 8, 2, 10, 12, 4, 14, 6, 3, 11, 1, 9, 15, 7, 13, 5]
+        );
+        let mut thresholds = matrix(3);
+        thresholds.sort();
+        assert!(thresholds.into_iter().eq(0..64));
+        assert_eq!(bayer(9, 3, 3), bayer(1, 3, 3));
+    }
+
+    #[test]
+    fn ordered_dithering_keeps_the_average() {
+        assert_eq!(whites(Color::rgb(100, 100, 100), Dither::None), 0);
+        assert_eq!(whites(Color::rgb(128, 128, 128), Dither::Bayer2), 32);
+        assert_eq!(whites(Color::rgb(64, 64, 64), Dither::Bayer4), 16);
+        assert_eq!(whites(Color::rgb(64, 64, 64), Dither::Bayer8), 16);
+    }
+
+    #[test]
+    fn error_diffusion_keeps_the_average() {
+        let quarter = Color::rgb(64, 64, 64);
+        assert!(whites(quarter, Dither::FloydSteinberg).abs_diff(16) <= 1);
+        // part of the error is dropped, so fewer pixels cross over
+        let atkinson = whites(quarter, Dither::Atkinson);
+        assert!(atkinson > 0 && atkinson <= 16, "{atkinson}");
+    }
+
+    #[test]
+    fn palette_colors_and_alpha_are_kept() {
+        for method in [Dither::Bayer8, Dither::FloydSteinberg, Dither::Atkinson] {
+            assert_eq!(whites(WHITE, method), 64);
+            assert_eq!(whites(Color::BLACK, method), 0);
+        }
+        let mut pixels = buffer(2, 1, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 2, 1);
+        canvas.set_blend_mode(BlendMode::Replace);
+        canvas.clear(Color::rgba(200, 10, 10, 77));
+        canvas.dither_rect(Rect::new(1, 0, 1, 1), &MONO, Dither::FloydSteinberg);
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::rgba(200, 10, 10, 77)));
+        assert_eq!(canvas.get_pixel(1, 0), Some(Color::rgba(0, 0, 0, 77)));
+    }
+}
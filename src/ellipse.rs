@@ -0,0 +1,796 @@
+use std::{f32::consts::TAU, ops::Range};
+
+use crate::{
+    canvas::Canvas,
+    color::Color,
+    coverage::{sample_rows, to_coverage, CoverageRow},
+    gradient::Paint,
+    polygon::FillRule,
+    transform::Transform,
+};
+
+impl Canvas<'_> {
+    pub fn circle(&mut self, center_x: i32, center_y: i32, radius: u32, color: Color) {
+        self.ellipse(center_x, center_y, radius, radius, color);
+    }
+
+    pub fn fill_circle(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        radius: u32,
+        paint: impl Into<Paint>,
+    ) {
+        self.fill_ellipse(center_x, center_y, radius, radius, paint);
+    }
+
+    /// Outline of an axis aligned ellipse with the given radii. Under a
+    /// rotation or shear the outline is drawn as a polyline instead.
+    pub fn ellipse(&mut self, center_x: i32, center_y: i32, rx: u32, ry: u32, color: Color) {
+        let transform = self.transform();
+        if let Some((x, y, rx, ry)) = device_ellipse(&transform, center_x, center_y, rx, ry) {
+            self.device_ellipse(x, y, rx, ry, color);
+            return;
+        }
+        let center = pixel_center(center_x, center_y);
+        let mut points = edge_points(&transform, center, (rx as f32, ry as f32), 0.0, TAU);
+        points.pop();
+        let points: Vec<_> = points
+            .into_iter()
+            .map(|(x, y)| (x.floor() as i32, y.floor() as i32))
+            .collect();
+        self.device_polyline(&points, true, color);
+    }
+
+    pub fn fill_ellipse(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        rx: u32,
+        ry: u32,
+        paint: impl Into<Paint>,
+    ) {
+        let transform = self.transform();
+        self.with_paint(paint.into(), |canvas, color| {
+            if let Some((x, y, rx, ry)) = device_ellipse(&transform, center_x, center_y, rx, ry) {
+                canvas.device_fill_ellipse(x, y, rx, ry, color);
+                return;
+            }
+            let center = pixel_center(center_x, center_y);
+            let points = edge_points(&transform, center, edge_radii(rx, ry), 0.0, TAU);
+            canvas.device_polygon(&points, FillRule::NonZero, color);
+        });
+    }
+
+    /// Part of an ellipse outline from `start` to `end`, angles in radians
+    /// measured clockwise from the positive x axis as seen on screen.
+    #[allow(clippy::too_many_arguments)]
+    pub fn arc(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        rx: u32,
+        ry: u32,
+        start: f32,
+        end: f32,
+        color: Color,
+    ) {
+        let transform = self.transform();
+        if let Some((x, y, drx, dry)) = device_ellipse(&transform, center_x, center_y, rx, ry) {
+            if transform.xx > 0.0 && transform.yy > 0.0 {
+                let (start, end) = device_angles(&transform, start, end);
+                self.device_arc(x, y, drx, dry, start, end, color);
+                return;
+            }
+        }
+        let sweep = Sweep::new(start, end);
+        let center = pixel_center(center_x, center_y);
+        let radii = (rx as f32, ry as f32);
+        let points: Vec<_> = edge_points(&transform, center, radii, sweep.start, sweep.length)
+            .into_iter()
+            .map(|(x, y)| (x.floor() as i32, y.floor() as i32))
+            .collect();
+        self.device_polyline(&points, false, color);
+    }
+
+    /// Filled pie slice of an ellipse between `start` and `end`, angles as in
+    /// [`Canvas::arc`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn fill_arc(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        rx: u32,
+        ry: u32,
+        start: f32,
+        end: f32,
+        paint: impl Into<Paint>,
+    ) {
+        let transform = self.transform();
+        self.with_paint(paint.into(), |canvas, color| {
+            if let Some((x, y, drx, dry)) = device_ellipse(&transform, center_x, center_y, rx, ry) {
+                if transform.xx > 0.0 && transform.yy > 0.0 {
+                    let (start, end) = device_angles(&transform, start, end);
+                    canvas.device_fill_arc(x, y, drx, dry, start, end, color);
+                    return;
+                }
+            }
+            let sweep = Sweep::new(start, end);
+            let outline = sweep_outline(&transform, center_x, center_y, rx, ry, &sweep);
+            canvas.device_polygon(&outline, FillRule::NonZero, color);
+        });
+    }
+
+    pub(crate) fn device_ellipse(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        rx: u32,
+        ry: u32,
+        color: Color,
+    ) {
+        if !self.ellipse_visible(center_x, center_y, rx, ry) {
+            return;
+        }
+        let (columns, rows) = self.visible_offsets(center_x, center_y);
+        if self.antialias() {
+            for (x, y, coverage) in smooth_quadrant(rx, ry, &columns, &rows) {
+                for (px, py) in mirrored(x, y) {
+                    if let Some((x, y)) = offset_pixel(center_x, center_y, px, py) {
+                        self.blend_pixel(x, y, color, coverage);
+                    }
+                }
+            }
+            return;
+        }
+        for (x, y) in Quadrant::new(rx, ry).points(&columns, &rows) {
+            self.plot_symmetric(center_x, center_y, x, y, color);
+        }
+    }
+
+    pub(crate) fn device_fill_ellipse(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        rx: u32,
+        ry: u32,
+        color: Color,
+    ) {
+        if !self.ellipse_visible(center_x, center_y, rx, ry) {
+            return;
+        }
+        if self.antialias() {
+            self.fill_smooth_ellipse(center_x, center_y, rx, ry, color);
+            return;
+        }
+        let (_, rows) = self.visible_offsets(center_x, center_y);
+        let quadrant = Quadrant::new(rx, ry);
+        for y in rows.within(0, quadrant.b) {
+            let (y, half_width) = (y as i32, quadrant.half_width(y) as i32);
+            let left = center_x.saturating_sub(half_width);
+            let right = center_x.saturating_add(half_width);
+            // the center row only once
+            let rows = if y == 0 { 1 } else { 2 };
+            for row in [center_y.checked_add(y), center_y.checked_sub(y)]
+                .into_iter()
+                .take(rows)
+                .flatten()
+            {
+                self.device_hline(left, right, row, color);
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn device_arc(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        rx: u32,
+        ry: u32,
+        start: f32,
+        end: f32,
+        color: Color,
+    ) {
+        if !self.ellipse_visible(center_x, center_y, rx, ry) {
+            return;
+        }
+        let sweep = Sweep::new(start, end);
+        let (columns, rows) = self.visible_offsets(center_x, center_y);
+        if self.antialias() {
+            for (x, y, coverage) in smooth_quadrant(rx, ry, &columns, &rows) {
+                for (px, py) in mirrored(x, y) {
+                    if !sweep.contains(px, py) {
+                        continue;
+                    }
+                    if let Some((x, y)) = offset_pixel(center_x, center_y, px, py) {
+                        self.blend_pixel(x, y, color, coverage);
+                    }
+                }
+            }
+            return;
+        }
+        for (x, y) in Quadrant::new(rx, ry).points(&columns, &rows) {
+            for (px, py) in mirrored(x, y) {
+                if !sweep.contains(px, py) {
+                    continue;
+                }
+                if let Some((x, y)) = offset_pixel(center_x, center_y, px, py) {
+                    self.device_pixel(x, y, color);
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn device_fill_arc(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        rx: u32,
+        ry: u32,
+        start: f32,
+        end: f32,
+        color: Color,
+    ) {
+        if !self.ellipse_visible(center_x, center_y, rx, ry) {
+            return;
+        }
+        let sweep = Sweep::new(start, end);
+        if self.antialias() {
+            if sweep.length >= TAU {
+                self.fill_smooth_ellipse(center_x, center_y, rx, ry, color);
+            } else {
+                let identity = Transform::IDENTITY;
+                let outline = sweep_outline(&identity, center_x, center_y, rx, ry, &sweep);
+                self.device_polygon(&outline, FillRule::NonZero, color);
+            }
+            return;
+        }
+        let clip = self.clip();
+        let (_, rows) = self.visible_offsets(center_x, center_y);
+        let quadrant = Quadrant::new(rx, ry);
+        for y in rows.within(0, quadrant.b) {
+            let (y, half_width) = (y as i32, quadrant.half_width(y) as i32);
+            // the center row only once
+            let rows = if y == 0 { 1 } else { 2 };
+            for dy in [y, -y].into_iter().take(rows) {
+                let Some(row) = center_y.checked_add(dy) else {
+                    continue;
+                };
+                if row < clip.y || row >= clip.bottom() {
+                    continue;
+                }
+                let left = center_x.saturating_sub(half_width).max(clip.x);
+                let right = center_x.saturating_add(half_width).min(clip.right() - 1);
+                for x in left..=right {
+                    if sweep.contains(x - center_x, dy) {
+                        self.device_pixel(x, row, color);
+                    }
+                }
+            }
+        }
+    }
+
+    // anti-aliased fill, sampled on sub-scanlines
+    fn fill_smooth_ellipse(
+        &mut self,
+        center_x: i32,
+        center_y: i32,
+        rx: u32,
+        ry: u32,
+        color: Color,
+    ) {
+        let (cx, cy) = pixel_center(center_x, center_y);
+        let (a, b) = edge_radii(rx, ry);
+        let clip = self.clip();
+        let left = ((cx - a).floor() as i32).max(clip.x);
+        let right = ((cx + a).ceil() as i32).min(clip.right());
+        let top = ((cy - b).floor() as i32).max(clip.y);
+        let bottom = ((cy + b).ceil() as i32).min(clip.bottom());
+        let mut row = CoverageRow::new(left, right);
+        for y in top..bottom {
+            row.clear();
+            for sample in sample_rows(y) {
+                let dy = (sample - cy) / b;
+                if dy.abs() < 1.0 {
+                    let half_width = a * (1.0 - dy * dy).sqrt();
+                    row.add_span(cx - half_width, cx + half_width);
+                }
+            }
+            self.blend_row(y, &row, color);
+        }
+    }
+
+    // cheap bounding box test before rasterizing
+    fn ellipse_visible(&self, center_x: i32, center_y: i32, rx: u32, ry: u32) -> bool {
+        // smooth edges reach one pixel further
+        let margin = self.antialias() as i64;
+        let (rx, ry) = (rx as i64 + margin, ry as i64 + margin);
+        let (x, y) = (center_x as i64, center_y as i64);
+        let clip = self.clip();
+        x + rx >= clip.x as i64
+            && x - rx < clip.right() as i64
+            && y + ry >= clip.y as i64
+            && y - ry < clip.bottom() as i64
+    }
+
+    // offsets from the center, as columns and rows, that can land in the clip
+    fn visible_offsets(&self, center_x: i32, center_y: i32) -> (Offsets, Offsets) {
+        let clip = self.clip();
+        (
+            Offsets::new(center_x, clip.x, clip.right()),
+            Offsets::new(center_y, clip.y, clip.bottom()),
+        )
+    }
+
+    // plots a first quadrant point in all four quadrants, each pixel once
+    fn plot_symmetric(&mut self, center_x: i32, center_y: i32, x: i32, y: i32, color: Color) {
+        for (px, py) in mirrored(x, y) {
+            if let Some((x, y)) = offset_pixel(center_x, center_y, px, py) {
+                self.device_pixel(x, y, color);
+            }
+        }
+    }
+}
+
+// pixel at an offset from the center, none beyond the i32 range
+fn offset_pixel(center_x: i32, center_y: i32, x: i32, y: i32) -> Option<(i32, i32)> {
+    Some((center_x.checked_add(x)?, center_y.checked_add(y)?))
+}
+
+// offsets from a center coordinate that land in start..end on either side,
+// as up to two ranges that don't overlap
+struct Offsets([Range<i64>; 2]);
+
+impl Offsets {
+    fn new(center: i32, start: i32, end: i32) -> Self {
+        let (center, start, end) = (center as i64, start as i64, end as i64);
+        let after = (start - center).max(0)..end - center;
+        let before = (center - end + 1).max(0)..center - start + 1;
+        let (first, second) = if before.start < after.start {
+            (before, after)
+        } else {
+            (after, before)
+        };
+        if first.is_empty() || second.is_empty() || second.start > first.end {
+            Self([first, second])
+        } else {
+            Self([first.start..first.end.max(second.end), 0..0])
+        }
+    }
+
+    // the offsets in first..=last
+    fn within(&self, first: i64, last: i64) -> impl Iterator<Item = i64> + '_ {
+        self.0
+            .iter()
+            .flat_map(move |range| range.start.max(first)..range.end.min(last + 1))
+    }
+}
+
+// the distinct reflections of a first quadrant point
+fn mirrored(x: i32, y: i32) -> impl Iterator<Item = (i32, i32)> {
+    [(x, y), (-x, y), (x, -y), (-x, -y)]
+        .into_iter()
+        .enumerate()
+        .filter(move |(i, _)| match i {
+            1 => x != 0,
+            2 => y != 0,
+            3 => x != 0 && y != 0,
+            _ => true,
+        })
+        .map(|(_, point)| point)
+}
+
+// angular range of an arc, normalized so it can be tested with one comparison
+struct Sweep {
+    start: f32,
+    length: f32,
+}
+
+impl Sweep {
+    fn new(start: f32, end: f32) -> Self {
+        let length = if end - start >= TAU {
+            TAU
+        } else {
+            (end - start).rem_euclid(TAU)
+        };
+        Self {
+            start: start.rem_euclid(TAU),
+            length,
+        }
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        if self.length >= TAU || (x == 0 && y == 0) {
+            return true;
+        }
+        // screen y points down, so this angle grows clockwise
+        let angle = (y as f32).atan2(x as f32);
+        (angle - self.start).rem_euclid(TAU) <= self.length
+    }
+}
+
+// center of a pixel in continuous coordinates
+fn pixel_center(x: i32, y: i32) -> (f32, f32) {
+    (x as f32 + 0.5, y as f32 + 0.5)
+}
+
+// the outline runs through pixel centers, so a smooth fill ends half a pixel
+// further out to cover it
+fn edge_radii(rx: u32, ry: u32) -> (f32, f32) {
+    (rx as f32 + 0.5, ry as f32 + 0.5)
+}
+
+// center and radii in canvas pixels when the transform keeps the ellipse
+// axis aligned
+fn device_ellipse(
+    transform: &Transform,
+    center_x: i32,
+    center_y: i32,
+    rx: u32,
+    ry: u32,
+) -> Option<(i32, i32, u32, u32)> {
+    if !transform.is_axis_aligned() {
+        return None;
+    }
+    let rx = (rx as f32 * transform.xx.abs()).round();
+    let ry = (ry as f32 * transform.yy.abs()).round();
+    // too large to rasterize exactly, left to the polygon path
+    if rx > MAX_RADIUS as f32 || ry > MAX_RADIUS as f32 {
+        return None;
+    }
+    let (x, y) = transform.apply_pixel(center_x, center_y);
+    Some((x, y, rx as u32, ry as u32))
+}
+
+// arc angles after a positive axis aligned scale, whole turns are kept
+fn device_angles(transform: &Transform, start: f32, end: f32) -> (f32, f32) {
+    let map = |angle: f32| {
+        let (sin, cos) = angle.sin_cos();
+        (transform.yy * sin).atan2(transform.xx * cos)
+    };
+    let start_mapped = map(start);
+    if end - start >= TAU {
+        (start_mapped, start_mapped + TAU)
+    } else {
+        (start_mapped, map(end))
+    }
+}
+
+// points along the edge of an ellipse from `start` over `length` radians,
+// transformed and about two canvas pixels apart
+fn edge_points(
+    transform: &Transform,
+    center: (f32, f32),
+    radii: (f32, f32),
+    start: f32,
+    length: f32,
+) -> Vec<(f32, f32)> {
+    let (cx, cy) = center;
+    let (a, b) = radii;
+    let size = a.max(b) * transform.length_scale();
+    let segments = (length * size / 2.0).ceil().clamp(4.0, 1024.0) as usize;
+    (0..=segments)
+        .map(|i| {
+            let angle = start + length * i as f32 / segments as f32;
+            let (sin, cos) = angle.sin_cos();
+            let (x, y) = if a == 0.0 || b == 0.0 {
+                (a * cos, b * sin)
+            } else {
+                // distance to the edge along the angle, not the parametric angle
+                let radius = a * b / ((b * cos).powi(2) + (a * sin).powi(2)).sqrt();
+                (radius * cos, radius * sin)
+            };
+            transform.apply(cx + x, cy + y)
+        })
+        .collect()
+}
+
+// polygon of a pie slice, the center followed by points along the edge
+fn sweep_outline(
+    transform: &Transform,
+    center_x: i32,
+    center_y: i32,
+    rx: u32,
+    ry: u32,
+    sweep: &Sweep,
+) -> Vec<(f32, f32)> {
+    let (cx, cy) = pixel_center(center_x, center_y);
+    let mut points = vec![transform.apply(cx, cy)];
+    let radii = edge_radii(rx, ry);
+    points.extend(edge_points(
+        transform,
+        (cx, cy),
+        radii,
+        sweep.start,
+        sweep.length,
+    ));
+    points
+}
+
+// anti-aliased outline of the first quadrant, the coverage of a pixel falls
+// off with its distance to the ellipse
+fn smooth_quadrant(rx: u32, ry: u32, columns: &Offsets, rows: &Offsets) -> Vec<(i32, i32, u8)> {
+    if rx == 0 || ry == 0 {
+        return Quadrant::new(rx, ry)
+            .points(columns, rows)
+            .map(|(x, y)| (x, y, 255))
+            .collect();
+    }
+    let (a, b) = (rx as f32, ry as f32);
+    let width = |y: f32| a * (1.0 - (y / b).powi(2)).max(0.0).sqrt();
+    let mut points = Vec::new();
+    for y in rows.within(0, ry as i64 + 1) {
+        let y = y as f32;
+        // the outline passes through this row somewhere between these columns
+        let left = (width(y + 1.0).floor() as i64 - 1).max(0);
+        let right = width(y - 1.0).ceil() as i64 + 1;
+        for x in columns.within(left, right) {
+            let x = x as f32;
+            // implicit ellipse divided by its gradient approximates the distance
+            let f = (x / a).powi(2) + (y / b).powi(2) - 1.0;
+            let gradient = (2.0 * x / (a * a)).hypot(2.0 * y / (b * b));
+            if gradient == 0.0 {
+                continue;
+            }
+            let coverage = to_coverage(1.0 - (f / gradient).abs());
+            if coverage > 0 {
+                points.push((x as i32, y as i32, coverage));
+            }
+        }
+    }
+    points
+}
+
+// largest radius rasterized exactly, the decision terms grow with its fourth
+// power and have to fit in an i128
+const MAX_RADIUS: u32 = 1 << 29;
+
+// outline of the first quadrant as the midpoint ellipse algorithm draws it,
+// one point per column where the slope is shallower than -1 and one per row
+// where it is steeper. Every point is worked out on its own, so only the
+// columns and rows that can be seen need to be visited. The algorithm moves
+// at most one pixel on the other axis per step, where the outline jumps
+// further it lags behind, which is kept so thin ellipses stay connected.
+struct Quadrant {
+    a: i64,
+    b: i64,
+    a2: i128,
+    b2: i128,
+    // first column of the steep part and the row it starts on
+    split_x: i64,
+    split_y: i64,
+}
+
+impl Quadrant {
+    // radii beyond MAX_RADIUS are cut down to it
+    fn new(rx: u32, ry: u32) -> Self {
+        let (a, b) = (rx.min(MAX_RADIUS) as i64, ry.min(MAX_RADIUS) as i64);
+        let mut quadrant = Self {
+            a,
+            b,
+            a2: (a * a) as i128,
+            b2: (b * b) as i128,
+            split_x: 0,
+            split_y: b,
+        };
+        if b == 0 {
+            // a single row, all of it shallow
+            quadrant.split_x = a + 1;
+            quadrant.split_y = -1;
+        } else if a > 0 {
+            // the slope passes -1 where b²x catches up with a²y
+            let (mut low, mut high) = (0, a);
+            while low < high {
+                let mid = (low + high) / 2;
+                if quadrant.b2 * mid as i128 >= quadrant.a2 * quadrant.lagging_column(mid) as i128 {
+                    high = mid;
+                } else {
+                    low = mid + 1;
+                }
+            }
+            quadrant.split_x = low;
+            quadrant.split_y = quadrant.lagging_column(low);
+        }
+        quadrant
+    }
+
+    // outline points in the given columns of the shallow part and rows of the
+    // steep part
+    fn points<'a>(
+        &'a self,
+        columns: &'a Offsets,
+        rows: &'a Offsets,
+    ) -> impl Iterator<Item = (i32, i32)> + 'a {
+        let shallow = columns
+            .within(0, self.split_x - 1)
+            .map(|x| (x, self.column(x)));
+        let steep = rows.within(0, self.split_y).map(|y| (self.row(y), y));
+        shallow.chain(steep).map(|(x, y)| (x as i32, y as i32))
+    }
+
+    // row of the shallow part's point in column x, the lowest one whose
+    // upper half is still inside
+    fn column(&self, x: i64) -> i64 {
+        let (a2, b2, x) = (self.a2, self.b2, x as i128);
+        // 4a²b² - 4b²x² > a²(2y - 1)²
+        let room = 4 * b2 * (a2 - x * x);
+        let estimate = (room as f64 / a2 as f64).sqrt() / 2.0 + 0.5;
+        last_inside(estimate, self.b, |y| {
+            let y = 2 * y as i128 - 1;
+            a2 * y * y < room
+        })
+        .unwrap_or(0)
+    }
+
+    // row in column x one step after the previous column, which the steep
+    // part starts from
+    fn lagging_column(&self, x: i64) -> i64 {
+        match x {
+            0 => self.column(0),
+            _ => self.column(x).max(self.column(x - 1) - 1),
+        }
+    }
+
+    // column of the steep part's point in row y, the first one whose right
+    // half is outside, or one step after the row above while catching up
+    fn row(&self, y: i64) -> i64 {
+        let catching_up = self.split_x + (self.split_y - y);
+        let (a2, b2, y) = (self.a2, self.b2, y as i128);
+        // 4a²b² - 4a²y² >= b²(2x + 1)²
+        let room = 4 * a2 * (b2 - y * y);
+        let estimate = (room as f64 / b2 as f64).sqrt() / 2.0 - 0.5;
+        let inside = last_inside(estimate, self.a, |x| {
+            let x = 2 * x as i128 + 1;
+            b2 * x * x <= room
+        });
+        let exact = inside.map_or(0, |x| x + 1);
+        exact.min(catching_up).max(self.split_x)
+    }
+
+    // widest outline point in row y
+    fn half_width(&self, y: i64) -> i64 {
+        if self.b == 0 {
+            return self.a;
+        }
+        if y <= self.split_y {
+            return self.row(y);
+        }
+        // the last column of the shallow part that still reaches this row
+        let (a2, b2, y) = (self.a2, self.b2, 2 * y as i128 - 1);
+        let room = 4 * a2 * b2 - a2 * y * y;
+        let estimate = (room as f64 / (4 * b2) as f64).sqrt();
+        last_inside(estimate, self.split_x - 1, |x| {
+            let x = x as i128;
+            4 * b2 * x * x < room
+        })
+        .unwrap_or(0)
+    }
+}
+
+// largest n in 0..=max that is `inside`, searching from a close estimate;
+// `inside` has to hold for every n below it too
+fn last_inside(estimate: f64, max: i64, inside: impl Fn(i64) -> bool) -> Option<i64> {
+    let mut n = (estimate as i64).clamp(0, max.max(0));
+    while n > 0 && !inside(n) {
+        n -= 1;
+    }
+    while n < max && inside(n + 1) {
+        n += 1;
+    }
+    inside(n).then_some(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        canvas::tests::{buffer, painted},
+        format::PixelFormat,
+    };
+
+    const RED: Color = Color::rgb(255, 0, 0);
+    const BLUE: Color = Color::rgb(0, 0, 255);
+
+    // outline points of the first quadrant from the plain midpoint algorithm
+    fn midpoint(rx: u32, ry: u32) -> Vec<(i32, i32)> {
+        let (a, b) = (rx as i64, ry as i64);
+        let (a2, b2) = (a * a, b * b);
+        let mut points = Vec::new();
+        let (mut x, mut y) = (0, b);
+        let mut p = 4 * b2 - 4 * a2 * b + a2;
+        while b2 * x < a2 * y {
+            points.push((x as i32, y as i32));
+            x += 1;
+            if p < 0 {
+                p += 4 * (2 * b2 * x + b2);
+            } else {
+                y -= 1;
+                p += 4 * (2 * b2 * x - 2 * a2 * y + b2);
+            }
+        }
+        let mut p = b2 * (2 * x + 1) * (2 * x + 1) + 4 * a2 * (y - 1) * (y - 1) - 4 * a2 * b2;
+        while y >= 0 {
+            points.push((x as i32, y as i32));
+            y -= 1;
+            if p > 0 {
+                p += 4 * (a2 - 2 * a2 * y);
+            } else {
+                x += 1;
+                p += 4 * (2 * b2 * x - 2 * a2 * y + a2);
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn quadrant_matches_the_midpoint_algorithm() {
+        let all = Offsets::new(0, -1000, 1000);
+        for rx in 1..60 {
+            for ry in 1..60 {
+                let mut points: Vec<_> = Quadrant::new(rx, ry).points(&all, &all).collect();
+                let mut expected = midpoint(rx, ry);
+                points.sort();
+                expected.sort();
+                assert_eq!(points, expected, "radii {rx}, {ry}");
+            }
+        }
+    }
+
+    #[test]
+    fn fills_cover_their_outline() {
+        for (rx, ry) in [(0, 3), (3, 0), (1, 5), (7, 4), (10, 10)] {
+            let mut pixels = buffer(32, 32, PixelFormat::Rgba8Srgb);
+            let mut canvas = Canvas::new(&mut pixels, 32, 32);
+            canvas.fill_ellipse(16, 16, rx, ry, BLUE);
+            canvas.ellipse(16, 16, rx, ry, RED);
+            let outline = painted(&canvas, RED);
+            assert!(!outline.is_empty());
+            let (left, right) = (16 - rx as i32, 16 + rx as i32);
+            for y in 16 - ry as i32..=16 + ry as i32 {
+                let row: Vec<_> = (left..=right)
+                    .filter(|&x| canvas.get_pixel(x, y) != Some(Color::rgba(0, 0, 0, 0)))
+                    .collect();
+                // each row is one run from the left to the right outline
+                assert_eq!(
+                    row.len(),
+                    row.last().map_or(0, |last| last - row[0] + 1) as usize
+                );
+                assert!(outline.contains(&(row[0], y)));
+            }
+        }
+    }
+
+    #[test]
+    fn huge_circles_are_clipped_without_overflow() {
+        let radius = 40_000;
+        let rows = |range: std::ops::Range<i32>| -> Vec<_> {
+            range.flat_map(|y| (0..64).map(move |x| (x, y))).collect()
+        };
+
+        let mut pixels = buffer(64, 64, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 64, 64);
+        canvas.fill_circle(32, radius + 22, radius as u32, RED);
+        assert_eq!(painted(&canvas, RED), rows(22..64));
+
+        let mut pixels = buffer(64, 64, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 64, 64);
+        canvas.circle(32, radius + 22, radius as u32, RED);
+        canvas.arc(32, radius + 22, radius as u32, radius as u32, 3.5, 6.0, RED);
+        assert_eq!(painted(&canvas, RED), rows(22..23));
+
+        for antialias in [false, true] {
+            let mut pixels = buffer(64, 64, PixelFormat::Rgba8Srgb);
+            let mut canvas = Canvas::new(&mut pixels, 64, 64);
+            canvas.set_antialias(antialias);
+            canvas.with_transform(Transform::scale(1e4, 1e4), |canvas| {
+                canvas.circle(0, 0, 40, RED);
+                canvas.fill_arc(0, 0, 40, 40, 0.0, 1.0, RED);
+            });
+            canvas.circle(i32::MIN, i32::MAX, u32::MAX, RED);
+            canvas.fill_circle(0, 0, u32::MAX, BLUE);
+            assert_eq!(painted(&canvas, BLUE), rows(0..64));
+        }
+    }
+}
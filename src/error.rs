@@ -0,0 +1,94 @@
+use crate::format::PixelFormat;
+use std::fmt;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::JsValue;
+
+/// Everything that can go wrong while setting up the window and renderer.
+#[derive(Debug)]
+pub enum Error {
+    /// No graphics adapter compatible with the surface was found.
+    NoAdapter,
+    /// The adapter refused to hand out a device.
+    RequestDevice(wgpu::RequestDeviceError),
+    /// A surface could not be created for the window.
+    CreateSurface(wgpu::CreateSurfaceError),
+    /// The surface does not support any format the adapter can render to.
+    UnsupportedFormat,
+    /// The adapter can't sample or upload textures in the requested pixel format.
+    UnsupportedPixelFormat(PixelFormat),
+    /// The event loop could not be created or failed while running.
+    EventLoop(winit::error::EventLoopError),
+    /// The window could not be built.
+    Window(winit::error::OsError),
+    /// A font file or glyph sheet could not be read.
+    InvalidFont(String),
+    /// A string is neither a hex color nor a known color name.
+    InvalidColor(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoAdapter => write!(f, "no compatible graphics adapter found"),
+            Error::RequestDevice(err) => write!(f, "failed to request device: {err}"),
+            Error::CreateSurface(err) => write!(f, "failed to create surface: {err}"),
+            Error::UnsupportedFormat => write!(f, "surface has no supported texture format"),
+            Error::UnsupportedPixelFormat(format) => {
+                write!(f, "pixel format {format:?} is not supported by the adapter")
+            }
+            Error::EventLoop(err) => write!(f, "event loop error: {err}"),
+            Error::Window(err) => write!(f, "failed to create window: {err}"),
+            Error::InvalidFont(reason) => write!(f, "invalid font: {reason}"),
+            Error::InvalidColor(color) => write!(f, "invalid color: {color:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::RequestDevice(err) => Some(err),
+            Error::CreateSurface(err) => Some(err),
+            Error::EventLoop(err) => Some(err),
+            Error::Window(err) => Some(err),
+            Error::NoAdapter
+            | Error::UnsupportedFormat
+            | Error::UnsupportedPixelFormat(_)
+            | Error::InvalidFont(_)
+            | Error::InvalidColor(_) => None,
+        }
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for Error {
+    fn from(err: wgpu::RequestDeviceError) -> Self {
+        Error::RequestDevice(err)
+    }
+}
+
+impl From<wgpu::CreateSurfaceError> for Error {
+    fn from(err: wgpu::CreateSurfaceError) -> Self {
+        Error::CreateSurface(err)
+    }
+}
+
+impl From<winit::error::EventLoopError> for Error {
+    fn from(err: winit::error::EventLoopError) -> Self {
+        Error::EventLoop(err)
+    }
+}
+
+impl From<winit::error::OsError> for Error {
+    fn from(err: winit::error::OsError) -> Self {
+        Error::Window(err)
+    }
+}
+
+// lets wasm_bindgen(start) report the error to the console
+#[cfg(target_arch = "wasm32")]
+impl From<Error> for JsValue {
+    fn from(err: Error) -> Self {
+        JsValue::from_str(&err.to_string())
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{canvas::Canvas, color::Color};
+
+/// Which neighbors of a pixel a fill spreads to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Connectivity {
+    /// Left, right, up and down.
+    #[default]
+    Four,
+    /// The four sides and the diagonals.
+    Eight,
+}
+
+impl Canvas<'_> {
+    /// Bucket fill of the region around `x, y` whose pixels differ from the
+    /// seed pixel by at most `tolerance` in every channel, 0 matches exactly.
+    pub fn flood_fill(
+        &mut self,
+        x: i32,
+        y: i32,
+        color: Color,
+        connectivity: Connectivity,
+        tolerance: u8,
+    ) {
+        let (x, y) = self.transform().apply_pixel(x, y);
+        let Some(target) = self.index(x, y).map(|index| self.decode(index)) else {
+            return;
+        };
+        let matches = |pixel: Color| {
+            let difference = |a: u8, b: u8| a.abs_diff(b) <= tolerance;
+            difference(pixel.r, target.r)
+                && difference(pixel.g, target.g)
+                && difference(pixel.b, target.b)
+                && difference(pixel.a, target.a)
+        };
+        self.scanline_fill(x, y, color, connectivity, matches);
+    }
+
+    /// Fills outward from `x, y` up to pixels of the `border` color.
+    pub fn boundary_fill(
+        &mut self,
+        x: i32,
+        y: i32,
+        color: Color,
+        border: Color,
+        connectivity: Connectivity,
+    ) {
+        let (x, y) = self.transform().apply_pixel(x, y);
+        self.scanline_fill(x, y, color, connectivity, |pixel| pixel != border);
+    }
+
+    // span based fill with an explicit stack of seeds, each pixel is tested
+    // against the buffer as it was before the fill
+    fn scanline_fill(
+        &mut self,
+        x: i32,
+        y: i32,
+        color: Color,
+        connectivity: Connectivity,
+        inside: impl Fn(Color) -> bool,
+    ) {
+        let clip = self.clip();
+        if !clip.contains(x, y) {
+            return;
+        }
+        let width = clip.width as usize;
+        let cell = |x: i32, y: i32| (y - clip.y) as usize * width + (x - clip.x) as usize;
+        let mut visited = vec![false; width * clip.height as usize];
+        let open = |canvas: &Self, visited: &[bool], x: i32, y: i32| {
+            !visited[cell(x, y)] && canvas.index(x, y).is_some_and(|i| inside(canvas.decode(i)))
+        };
+        // diagonal neighbors sit one pixel past the ends of a span
+        let reach = match connectivity {
+            Connectivity::Four => 0,
+            Connectivity::Eight => 1,
+        };
+
+        let mut seeds = vec![(x, y)];
+        while let Some((x, y)) = seeds.pop() {
+            if !open(self, &visited, x, y) {
+                continue;
+            }
+            let mut left = x;
+            while left > clip.x && open(self, &visited, left - 1, y) {
+                left -= 1;
+            }
+            let mut right = x + 1;
+            while right < clip.right() && open(self, &visited, right, y) {
+                right += 1;
+            }
+            for x in left..right {
+                visited[cell(x, y)] = true;
+            }
+            self.write_span(y, left, right, color);
+
+            // one seed per run of open pixels in the rows above and below
+            for row in [y - 1, y + 1] {
+                if row < clip.y || row >= clip.bottom() {
+                    continue;
+                }
+                let mut in_run = false;
+                for x in (left - reach).max(clip.x)..(right + reach).min(clip.right()) {
+                    let is_open = open(self, &visited, x, row);
+                    if is_open && !in_run {
+                        seeds.push((x, row));
+                    }
+                    in_run = is_open;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        canvas::tests::{buffer, painted},
+        format::PixelFormat,
+        rect::Rect,
+    };
+
+    const RED: Color = Color::rgb(255, 0, 0);
+    const BLUE: Color = Color::rgb(0, 0, 255);
+
+    // a 5x5 canvas split by a diagonal wall of blue pixels
+    fn with_diagonal_wall(fill: impl FnOnce(&mut Canvas)) -> Vec<(i32, i32)> {
+        let mut pixels = buffer(5, 5, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 5, 5);
+        canvas.clear(Color::BLACK);
+        for i in 0..5 {
+            canvas.set_pixel(i, 4 - i, BLUE);
+        }
+        fill(&mut canvas);
+        painted(&canvas, RED)
+    }
+
+    #[test]
+    fn four_connected_fills_stop_at_diagonal_walls() {
+        let four = with_diagonal_wall(|canvas| {
+            canvas.flood_fill(0, 0, RED, Connectivity::Four, 0);
+        });
+        assert_eq!(four.len(), 10);
+        assert!(four.iter().all(|&(x, y)| x + y < 4));
+        let eight = with_diagonal_wall(|canvas| {
+            canvas.flood_fill(0, 0, RED, Connectivity::Eight, 0);
+        });
+        assert_eq!(eight.len(), 20);
+    }
+
+    #[test]
+    fn tolerance_widens_the_matched_colors() {
+        let mut pixels = buffer(3, 1, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 3, 1);
+        canvas.set_pixel(0, 0, Color::rgb(10, 10, 10));
+        canvas.set_pixel(1, 0, Color::rgb(14, 10, 6));
+        canvas.set_pixel(2, 0, Color::rgb(20, 10, 10));
+        let mut strict = pixels.clone();
+        let mut canvas = Canvas::new(&mut strict, 3, 1);
+        canvas.flood_fill(0, 0, RED, Connectivity::Four, 3);
+        assert_eq!(painted(&canvas, RED), [(0, 0)]);
+        let mut canvas = Canvas::new(&mut pixels, 3, 1);
+        canvas.flood_fill(0, 0, RED, Connectivity::Four, 4);
+        assert_eq!(painted(&canvas, RED), [(0, 0), (1, 0)]);
+    }
+
+    #[test]
+    fn boundary_fills_stop_at_the_border_color() {
+        let mut pixels = buffer(6, 6, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 6, 6);
+        canvas.fill_rect(Rect::new(0, 0, 5, 5), BLUE);
+        canvas.fill_rect(Rect::new(1, 1, 3, 3), Color::BLACK);
+        canvas.set_pixel(2, 2, Color::rgb(1, 2, 3));
+        canvas.boundary_fill(1, 1, RED, BLUE, Connectivity::Four);
+        let expected: Vec<_> = (1..4).flat_map(|y| (1..4).map(move |x| (x, y))).collect();
+        assert_eq!(painted(&canvas, RED), expected);
+        assert_eq!(canvas.get_pixel(5, 5), Some(Color::TRANSPARENT));
+    }
+
+    #[test]
+    fn fills_stay_inside_the_clip() {
+        let mut pixels = buffer(4, 4, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 4, 4);
+        canvas.with_clip(Rect::new(1, 1, 2, 2), |canvas| {
+            canvas.flood_fill(1, 1, RED, Connectivity::Eight, 0);
+            canvas.flood_fill(0, 0, BLUE, Connectivity::Eight, 0);
+        });
+        assert_eq!(painted(&canvas, RED), [(1, 1), (2, 1), (1, 2), (2, 2)]);
+        assert!(painted(&canvas, BLUE).is_empty());
+    }
+}
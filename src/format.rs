@@ -0,0 +1,39 @@
+/// Layout of the bytes in `State::pixels`, independent of the format the
+/// surface prefers. The shader converts to whatever the swapchain uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PixelFormat {
+    /// 8 bit red, green, blue, alpha with sRGB encoded color channels.
+    #[default]
+    Rgba8Srgb,
+    /// 8 bit red, green, blue, alpha with linear color channels.
+    Rgba8Linear,
+    /// 8 bit blue, green, red, alpha with sRGB encoded color channels.
+    Bgra8Srgb,
+    /// 8 bit blue, green, red, alpha with linear color channels.
+    Bgra8Linear,
+    /// One byte per pixel indexing a [`Palette`] of sRGB colors, which the
+    /// shader looks up. Drawing with a color stores the nearest palette entry.
+    ///
+    /// [`Palette`]: crate::palette::Palette
+    Indexed8,
+}
+
+impl PixelFormat {
+    /// Format of the texture the pixel buffer is uploaded to.
+    pub fn texture_format(self) -> wgpu::TextureFormat {
+        match self {
+            PixelFormat::Rgba8Srgb => wgpu::TextureFormat::Rgba8UnormSrgb,
+            PixelFormat::Rgba8Linear => wgpu::TextureFormat::Rgba8Unorm,
+            PixelFormat::Bgra8Srgb => wgpu::TextureFormat::Bgra8UnormSrgb,
+            PixelFormat::Bgra8Linear => wgpu::TextureFormat::Bgra8Unorm,
+            PixelFormat::Indexed8 => wgpu::TextureFormat::R8Unorm,
+        }
+    }
+
+    pub fn bytes_per_pixel(self) -> u32 {
+        match self {
+            PixelFormat::Indexed8 => 1,
+            _ => 4,
+        }
+    }
+}
@@ -7,6 +7,12 @@ pub struct FpsCounter {
     fps: f64,
 }
 
+impl Default for FpsCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FpsCounter {
     pub fn new() -> Self {
         Self {
@@ -29,11 +35,11 @@ impl FpsCounter {
         (self.frame_time_ms, self.fps)
     }
 
-    pub fn fps(&self)->f64{
+    pub fn fps(&self) -> f64 {
         self.fps
     }
 
-    pub fn frame_time(&self)->f64{
+    pub fn frame_time(&self) -> f64 {
         self.frame_time_ms
     }
 }
@@ -0,0 +1,286 @@
+use std::f32::consts::TAU;
+
+use crate::{color::Color, transform::Transform};
+
+/// What a filled shape is painted with, a single color or a gradient. Fill
+/// methods take anything that converts into it, so a plain [`Color`] works
+/// as before.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Paint {
+    Solid(Color),
+    Gradient(Gradient),
+}
+
+impl From<Color> for Paint {
+    fn from(color: Color) -> Self {
+        Paint::Solid(color)
+    }
+}
+
+impl From<Gradient> for Paint {
+    fn from(gradient: Gradient) -> Self {
+        Paint::Gradient(gradient)
+    }
+}
+
+/// How a gradient continues outside the range of its stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Spread {
+    /// Keep the color of the first or last stop.
+    #[default]
+    Pad,
+    /// Start over from the first stop.
+    Repeat,
+    /// Run back and forth through the stops.
+    Reflect,
+}
+
+/// A color at a position along a gradient, 0 at its start and 1 at its end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorStop {
+    pub offset: f32,
+    pub color: Color,
+}
+
+/// Where a gradient runs, in the local coordinates of the draw call.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradientShape {
+    /// Along the line from `start` to `end`, constant across it.
+    Linear { start: (f32, f32), end: (f32, f32) },
+    /// Outward from `center`, reaching the end at `radius`.
+    Radial { center: (f32, f32), radius: f32 },
+    /// Clockwise around `center`, one full turn starting at `angle` radians
+    /// from the positive x axis.
+    Conic { center: (f32, f32), angle: f32 },
+}
+
+/// Smooth blend between color stops. Colors are interpolated with
+/// premultiplied alpha, so fading into a transparent stop doesn't darken.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub shape: GradientShape,
+    // sorted by offset, looking up a color depends on it
+    stops: Vec<ColorStop>,
+    pub spread: Spread,
+}
+
+impl Gradient {
+    pub fn new(shape: GradientShape) -> Self {
+        Self {
+            shape,
+            stops: Vec::new(),
+            spread: Spread::default(),
+        }
+    }
+
+    pub fn linear(start: (f32, f32), end: (f32, f32)) -> Self {
+        Self::new(GradientShape::Linear { start, end })
+    }
+
+    pub fn radial(center: (f32, f32), radius: f32) -> Self {
+        Self::new(GradientShape::Radial { center, radius })
+    }
+
+    pub fn conic(center: (f32, f32), angle: f32) -> Self {
+        Self::new(GradientShape::Conic { center, angle })
+    }
+
+    /// Adds a stop, after any existing ones at the same offset so two stops
+    /// there make a hard edge.
+    pub fn with_stop(mut self, offset: f32, color: Color) -> Self {
+        let index = self.stops.partition_point(|stop| stop.offset <= offset);
+        self.stops.insert(index, ColorStop { offset, color });
+        self
+    }
+
+    /// The stops added so far, sorted by offset.
+    pub fn stops(&self) -> &[ColorStop] {
+        &self.stops
+    }
+
+    pub fn with_spread(mut self, spread: Spread) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    /// Color at a point in local coordinates, transparent without stops.
+    pub fn color_at(&self, x: f32, y: f32) -> Color {
+        self.color_at_offset(self.offset(x, y))
+    }
+
+    // position along the gradient before the spread is applied
+    fn offset(&self, x: f32, y: f32) -> f32 {
+        match self.shape {
+            GradientShape::Linear { start, end } => {
+                let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+                let length_squared = dx * dx + dy * dy;
+                if length_squared == 0.0 {
+                    return 0.0;
+                }
+                ((x - start.0) * dx + (y - start.1) * dy) / length_squared
+            }
+            GradientShape::Radial { center, radius } => {
+                let distance = (x - center.0).hypot(y - center.1);
+                if radius <= 0.0 {
+                    return if distance > 0.0 { 1.0 } else { 0.0 };
+                }
+                distance / radius
+            }
+            GradientShape::Conic { center, angle } => {
+                // screen y points down, so this angle grows clockwise
+                let point = (y - center.1).atan2(x - center.0);
+                (point - angle).rem_euclid(TAU) / TAU
+            }
+        }
+    }
+
+    fn color_at_offset(&self, offset: f32) -> Color {
+        let (Some(first), Some(last)) = (self.stops.first(), self.stops.last()) else {
+            return Color::TRANSPARENT;
+        };
+        let t = match self.spread {
+            Spread::Pad => offset,
+            Spread::Repeat => offset.rem_euclid(1.0),
+            Spread::Reflect => {
+                let t = offset.rem_euclid(2.0);
+                if t > 1.0 {
+                    2.0 - t
+                } else {
+                    t
+                }
+            }
+        };
+        if t.is_nan() || t <= first.offset {
+            return first.color;
+        }
+        if t >= last.offset {
+            return last.color;
+        }
+        let next = self.stops.partition_point(|stop| stop.offset <= t);
+        let (from, to) = (self.stops[next - 1], self.stops[next]);
+        let weight = (t - from.offset) / (to.offset - from.offset);
+        from.color.lerp_premultiplied(to.color, weight)
+    }
+}
+
+// a gradient as it is applied to canvas pixels, for the duration of one fill
+pub(crate) struct Shader {
+    gradient: Gradient,
+    // canvas to local coordinates
+    inverse: Transform,
+}
+
+impl Shader {
+    // None when the transform collapses everything onto a line
+    pub(crate) fn new(gradient: Gradient, transform: &Transform) -> Option<Self> {
+        Some(Self {
+            gradient,
+            inverse: transform.inverse()?,
+        })
+    }
+
+    // color for the center of a canvas pixel
+    pub(crate) fn color_at(&self, x: i32, y: i32) -> Color {
+        let (x, y) = self.inverse.apply(x as f32 + 0.5, y as f32 + 0.5);
+        self.gradient.color_at(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        canvas::{tests::buffer, Canvas},
+        format::PixelFormat,
+        rect::Rect,
+    };
+
+    const RED: Color = Color::rgb(255, 0, 0);
+    const BLUE: Color = Color::rgb(0, 0, 255);
+
+    // red at 0 to blue at 1 along the x axis
+    fn red_to_blue(spread: Spread) -> Gradient {
+        Gradient::linear((0.0, 0.0), (1.0, 0.0))
+            .with_stop(1.0, BLUE)
+            .with_stop(0.0, RED)
+            .with_spread(spread)
+    }
+
+    #[test]
+    fn stops_stay_sorted() {
+        let gradient = red_to_blue(Spread::Pad).with_stop(0.5, Color::BLACK);
+        let offsets: Vec<_> = gradient.stops().iter().map(|stop| stop.offset).collect();
+        assert_eq!(offsets, [0.0, 0.5, 1.0]);
+        assert_eq!(gradient.color_at(0.25, 0.0), Color::rgb(128, 0, 0));
+        assert_eq!(
+            Gradient::linear((0.0, 0.0), (1.0, 0.0)).color_at(0.5, 0.0),
+            Color::TRANSPARENT
+        );
+    }
+
+    #[test]
+    fn spread_modes_outside_the_stops() {
+        let colors = |spread| {
+            let gradient = red_to_blue(spread);
+            [-0.25, 0.25, 1.25, 1.75].map(|x| gradient.color_at(x, 0.0))
+        };
+        let (quarter, three_quarters) = (RED.lerp(BLUE, 0.25), RED.lerp(BLUE, 0.75));
+        assert_eq!(colors(Spread::Pad), [RED, quarter, BLUE, BLUE]);
+        assert_eq!(
+            colors(Spread::Repeat),
+            [three_quarters, quarter, quarter, three_quarters]
+        );
+        assert_eq!(
+            colors(Spread::Reflect),
+            [quarter, quarter, three_quarters, quarter]
+        );
+    }
+
+    #[test]
+    fn equal_offsets_make_a_hard_edge() {
+        let gradient = Gradient::linear((0.0, 0.0), (1.0, 0.0))
+            .with_stop(0.0, RED)
+            .with_stop(0.5, RED)
+            .with_stop(0.5, BLUE)
+            .with_stop(1.0, BLUE);
+        assert_eq!(gradient.color_at(0.49, 0.0), RED);
+        assert_eq!(gradient.color_at(0.5, 0.0), BLUE);
+    }
+
+    #[test]
+    fn fading_to_transparent_keeps_the_color() {
+        let gradient = Gradient::linear((0.0, 0.0), (1.0, 0.0))
+            .with_stop(0.0, RED)
+            .with_stop(1.0, Color::TRANSPARENT);
+        assert_eq!(gradient.color_at(0.5, 0.0), Color::rgba(255, 0, 0, 128));
+    }
+
+    #[test]
+    fn radial_and_conic_offsets() {
+        let radial = Gradient::radial((0.0, 0.0), 2.0)
+            .with_stop(0.0, RED)
+            .with_stop(1.0, BLUE);
+        assert_eq!(radial.color_at(0.0, 1.0), RED.lerp(BLUE, 0.5));
+        assert_eq!(radial.color_at(-3.0, 0.0), BLUE);
+        let conic = Gradient::conic((0.0, 0.0), 0.0)
+            .with_stop(0.0, RED)
+            .with_stop(1.0, BLUE);
+        // a quarter turn clockwise is down on screen
+        assert_eq!(conic.color_at(0.0, 1.0), RED.lerp(BLUE, 0.25));
+    }
+
+    #[test]
+    fn fills_sample_pixel_centers_in_local_coordinates() {
+        let mut pixels = buffer(4, 1, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 4, 1);
+        let gradient = Gradient::linear((0.0, 0.0), (4.0, 0.0))
+            .with_stop(0.0, RED)
+            .with_stop(1.0, BLUE);
+        canvas.fill_rect(Rect::new(0, 0, 4, 1), gradient);
+        let expected = [0.125, 0.375, 0.625, 0.875].map(|t| Some(RED.lerp(BLUE, t)));
+        assert_eq!(
+            (0..4).map(|x| canvas.get_pixel(x, 0)).collect::<Vec<_>>(),
+            expected
+        );
+    }
+}
@@ -1,109 +1,100 @@
 use crate::{
-    demo::{demo_input_handler, demo_update_handler, Color, DemoApp, Shape},
+    app::App,
+    canvas::Canvas,
+    color::Color,
+    config::Config,
+    demo::{DemoApp, Shape},
+    error::Error,
+    format::PixelFormat,
     fps::FpsCounter,
+    palette::Palette,
+    rect::Rect,
+    scaling::{ScalingMode, Viewport},
 };
+use chrono::{DateTime, Utc};
 use wgpu::util::DeviceExt;
 use winit::{
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
     event::*,
-    dpi::LogicalSize,
     event_loop::EventLoop,
     keyboard::{KeyCode, PhysicalKey},
-    window::{Window, WindowBuilder},
+    window::{Fullscreen, Window, WindowBuilder},
 };
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
-const INITIAL_WINDOW: (u32, u32) = (640, 480); // Default size
-
-type InputHandler<T> = fn(&mut State<T>, &WindowEvent) -> bool;
-type UpdateFn<T> = fn(&mut State<T>);
+// where the pixel buffer ends up after the render pass
+enum RenderTarget<'a> {
+    Surface {
+        surface: wgpu::Surface<'a>,
+        config: wgpu::SurfaceConfiguration,
+        // window must be initialized after surface
+        // so it gets dropped after it as the surface
+        // contains unsafe references to the window's resources.
+        window: &'a Window,
+    },
+    // no window, frames are rendered into a texture that can be read back
+    Offscreen {
+        texture: wgpu::Texture,
+    },
+}
 
-pub struct State<'a, T> {
+pub struct State<'a> {
     // wgpu ctx
-    surface: wgpu::Surface<'a>,
+    target: RenderTarget<'a>,
     device: wgpu::Device,
     queue: wgpu::Queue,
-    config: wgpu::SurfaceConfiguration,
-    size: LogicalSize<u32>,
-
-    // window must be initialized after surface
-    // so it gets dropped after it as the surface
-    // contains unsafe references to the window's resources.
-    window: &'a Window,
+    size: PhysicalSize<u32>,
     clear_color: wgpu::Color,
 
     pub texture_extent: wgpu::Extent3d,
     texture: wgpu::Texture,
     pub pixels: Vec<u8>,
+    pixel_format: PixelFormat,
+    palette: Palette,
+    // only read by the shader for indexed buffers, but always bound
+    palette_texture: wgpu::Texture,
+    palette_dirty: bool,
     render_pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
     bind_group: wgpu::BindGroup,
+    sampler: wgpu::Sampler,
     vertex_buffer: wgpu::Buffer,
+    scaling: ScalingMode,
+    transform_buffer: wgpu::Buffer,
+    buffer_follows_window: bool,
+    preserve_buffer: bool,
+    dirty_tracking: bool,
+    dirty: Vec<Rect>,
 
-    // game ctx
+    title: String,
+    show_fps: bool,
     fps_counter: FpsCounter,
-    input_handler: InputHandler<T>,
-    update_fn: UpdateFn<T>,
-    pub game_context: &'a mut T,
+    last_update: DateTime<Utc>,
 }
 
-impl<'a, T> State<'a, T> {
+impl<'a> State<'a> {
     // Creating some of the wgpu types requires async code
-    async fn new(
-        window: &'a Window,
-        game_context: &'a mut T,
-        initial_size: LogicalSize<u32>,
-        input_handler: InputHandler<T>,
-        update_fn: UpdateFn<T>,
-    ) -> State<'a, T> {
-        let size = initial_size;
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            #[cfg(not(target_arch = "wasm32"))]
-            backends: wgpu::Backends::PRIMARY,
-            #[cfg(target_arch = "wasm32")]
-            backends: wgpu::Backends::GL,
-            ..Default::default()
-        });
-        let surface = instance.create_surface(window).unwrap();
-
-        // handle for the actual graphics card
-        // creates device and queue
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false, // do not use a software system
-            })
-            .await
-            .unwrap();
-
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    required_features: wgpu::Features::empty(),
-                    // webgl support
-                    required_limits: if cfg!(target_arch = "wasm32") {
-                        wgpu::Limits::downlevel_webgl2_defaults()
-                    } else {
-                        wgpu::Limits::default()
-                    },
-                    label: None,
-                    memory_hints: Default::default(),
-                },
-                None,
-            )
-            .await
-            .unwrap();
+    async fn new(window: &'a Window, settings: &Config) -> Result<State<'a>, Error> {
+        // the surface works in physical pixels, which differ from the
+        // requested logical size on HiDPI displays
+        let size = window.inner_size();
+        let instance = create_instance(wgpu::Backends::PRIMARY);
+        let surface = instance.create_surface(window)?;
+        let (adapter, device, queue) = request_device(&instance, Some(&surface)).await?;
+        check_pixel_format(&adapter, settings.pixel_format)?;
 
         let surface_caps = surface.get_capabilities(&adapter);
 
-        // assume srgb otherwise it will be darker
+        // prefer srgb, the shader encodes by hand otherwise
         let surface_format = surface_caps
             .formats
             .iter()
             .find(|f| f.is_srgb())
+            .or(surface_caps.formats.first())
             .copied()
-            .unwrap_or(surface_caps.formats[0]); // gets format for how gpu stores textures
+            .ok_or(Error::UnsupportedFormat)?; // gets format for how gpu stores textures
 
         // dbg
         // for format in surface_caps.formats.into_iter() {
@@ -111,6 +102,13 @@ impl<'a, T> State<'a, T> {
         // }
         // println!("Surface format: {:?}", surface_format);
 
+        // the Auto modes are resolved by wgpu, everything else has to be supported
+        let present_mode = match settings.present_mode {
+            mode @ (wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync) => mode,
+            mode if surface_caps.present_modes.contains(&mode) => mode,
+            _ => wgpu::PresentMode::Fifo, // always supported
+        };
+
         let config = wgpu::SurfaceConfiguration {
             // determines how underlying surface
             // textures are created
@@ -118,38 +116,71 @@ impl<'a, T> State<'a, T> {
             format: surface_format,
             width: size.width,
             height: size.height,
-            present_mode: surface_caps.present_modes[0], // how to sync the surface to display
-            // Fifo is VSync, but we select at runtime
+            present_mode, // how to sync the surface to display
             alpha_mode: surface_caps.alpha_modes[0],
             view_formats: vec![], // sRGB linear color space
             desired_maximum_frame_latency: 2,
         };
+        let target = RenderTarget::Surface {
+            surface,
+            config,
+            window,
+        };
+
+        Ok(Self::with_target(
+            target,
+            device,
+            queue,
+            surface_format,
+            size,
+            settings,
+        ))
+    }
+
+    /// Creates a state without a window that renders every frame into an
+    /// offscreen texture of `settings.window_size`. Frames go through the same
+    /// pipeline as the windowed path and can be fetched with [`State::read_frame`].
+    pub async fn new_headless(settings: &Config) -> Result<State<'a>, Error> {
+        let size = PhysicalSize::new(settings.window_size.width, settings.window_size.height);
+        // nothing is presented, so a GL adapter such as a software renderer
+        // on a machine without a GPU does as well
+        let instance = create_instance(wgpu::Backends::all());
+        let (adapter, device, queue) = request_device(&instance, None).await?;
+        check_pixel_format(&adapter, settings.pixel_format)?;
+
+        let format = OFFSCREEN_FORMAT;
+        let texture = create_offscreen_texture(&device, size, format);
+        let target = RenderTarget::Offscreen { texture };
+
+        Ok(Self::with_target(
+            target, device, queue, format, size, settings,
+        ))
+    }
+
+    // shared by windowed and headless creation, sets up the pixel buffer and pipeline
+    fn with_target(
+        target: RenderTarget<'a>,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        target_format: wgpu::TextureFormat,
+        size: PhysicalSize<u32>,
+        settings: &Config,
+    ) -> State<'a> {
         let clear_color = wgpu::Color::BLACK;
+        let buffer_size = settings.buffer_size();
+        let scaling = settings.scaling;
 
         let texture_extent = wgpu::Extent3d {
-            width: INITIAL_WINDOW.0,
-            height: INITIAL_WINDOW.1,
+            width: buffer_size.width,
+            height: buffer_size.height,
             depth_or_array_layers: 1,
         };
-        let texture_format = surface_format;
-
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("pixels_source_texture"),
-            size: texture_extent,
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: texture_format,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[],
-        });
+        let pixel_format = settings.pixel_format;
+        let texture_format = pixel_format.texture_format();
 
-        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let pixel_count = (size.width * size.height) as usize;
-        let mut pixels = Vec::with_capacity(pixel_count * 4); // 4 channels: RGBA
-        for _ in 0..pixel_count {
-            pixels.extend_from_slice(&[0, 0, 0, 255]); // Red color (RGBA)
-        }
+        let texture = create_pixel_texture(&device, texture_extent, texture_format);
+        let pixels = blank_pixels(texture_extent, pixel_format);
+        let palette_texture = create_palette_texture(&device);
 
         let shader = wgpu::include_wgsl!("shader.wgsl");
         let module = device.create_shader_module(shader);
@@ -170,12 +201,15 @@ impl<'a, T> State<'a, T> {
             border_color: None,
         });
         // Create vertex buffer; array-of-array of position and texture coordinates
-        let vertex_data: [[f32; 2]; 3] = [
-            // One full-screen triangle
-            // See: https://github.com/parasyte/pixels/issues/180
+        let vertex_data: [[f32; 2]; 6] = [
+            // One quad covering clip space, placed on the surface by the transform
+            // uniform so the buffer can be letterboxed or cropped
+            [-1.0, -1.0],
+            [1.0, -1.0],
+            [1.0, 1.0],
             [-1.0, -1.0],
-            [3.0, -1.0],
-            [-1.0, 3.0],
+            [1.0, 1.0],
+            [-1.0, 1.0],
         ];
         let vertex_data_slice: &[u8] = bytemuck::cast_slice(&vertex_data);
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -194,6 +228,14 @@ impl<'a, T> State<'a, T> {
             }],
         };
 
+        // scale and offset of the quad in clip space
+        let transform = scaling.viewport(buffer_size, size).clip_transform(size);
+        let transform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("pixels_scaling_renderer_transform_buffer"),
+            contents: bytemuck::cast_slice(&transform),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
         // Create bind group
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("pixels_scaling_renderer_bind_group_layout"),
@@ -214,24 +256,38 @@ impl<'a, T> State<'a, T> {
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                     count: None,
                 },
-            ],
-        });
-
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("pixels_scaling_renderer_bind_group"),
-            layout: &bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&texture_view),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
                 },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
                 },
             ],
         });
 
+        let bind_group = create_bind_group(
+            &device,
+            &bind_group_layout,
+            &texture,
+            &sampler,
+            &transform_buffer,
+            &palette_texture,
+        );
+
         // Create pipeline
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("pixels_scaling_renderer_pipeline_layout"),
@@ -253,10 +309,18 @@ impl<'a, T> State<'a, T> {
             fragment: Some(wgpu::FragmentState {
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
                 module: &module,
-                entry_point: "fs_main",
+                // non srgb swapchains need the sampled linear color encoded by
+                // hand, indexed buffers look their colors up in the palette
+                entry_point: match (pixel_format, target_format.is_srgb()) {
+                    (PixelFormat::Indexed8, true) => "fs_main_indexed",
+                    (PixelFormat::Indexed8, false) => "fs_main_indexed_encode_srgb",
+                    (_, true) => "fs_main",
+                    (_, false) => "fs_main_encode_srgb",
+                },
                 targets: &[Some(wgpu::ColorTargetState {
-                    format: texture_format,
-                    blend: Some(wgpu::BlendState::REPLACE),
+                    format: target_format,
+                    // translucent buffer pixels show the clear color behind them
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
             }),
@@ -265,85 +329,324 @@ impl<'a, T> State<'a, T> {
         });
         let fps_counter = FpsCounter::new();
 
-        return Self {
-            update_fn,
-            game_context,
+        Self {
+            title: settings.title.clone(),
+            show_fps: settings.show_fps,
+            pixel_format,
+            palette: settings.palette.clone(),
+            palette_texture,
+            // uploaded with the first frame
+            palette_dirty: true,
             fps_counter,
+            last_update: Utc::now(),
             vertex_buffer,
+            scaling,
+            transform_buffer,
+            buffer_follows_window: settings.buffer_follows_window,
+            preserve_buffer: settings.preserve_buffer,
+            dirty_tracking: settings.dirty_tracking,
+            // the texture starts out empty
+            dirty: vec![Rect::new(0, 0, texture_extent.width, texture_extent.height)],
             pixels,
             texture_extent,
             texture,
             clear_color,
-            window,
-            surface,
+            target,
             device,
             queue,
-            config,
             size,
-            input_handler,
+            bind_group_layout,
             bind_group,
+            sampler,
             render_pipeline,
-        };
+        }
     }
 
-    pub fn window(&self) -> &Window {
-        &self.window
+    /// The window being rendered to, `None` for headless states.
+    pub fn window(&self) -> Option<&Window> {
+        match &self.target {
+            RenderTarget::Surface { window, .. } => Some(window),
+            RenderTarget::Offscreen { .. } => None,
+        }
     }
 
-    pub fn resize(&mut self, new_size: LogicalSize<u32>) {
-        if new_size.width > 0 && new_size.height > 0 {
-            self.size = new_size;
-            self.config.width = new_size.width;
-            self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config); // resize
+    /// Layout of the bytes in [`State::pixels`].
+    pub fn pixel_format(&self) -> PixelFormat {
+        self.pixel_format
+    }
+
+    /// Colors the indices of a [`PixelFormat::Indexed8`] buffer refer to.
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// The palette for changes such as [`Palette::rotate`], which are
+    /// uploaded with the next render. Only has an effect with
+    /// [`PixelFormat::Indexed8`].
+    pub fn palette_mut(&mut self) -> &mut Palette {
+        self.palette_dirty = true;
+        &mut self.palette
+    }
+
+    /// Replaces the palette, the pixel data is left untouched.
+    pub fn set_palette(&mut self, palette: Palette) {
+        *self.palette_mut() = palette;
+    }
+
+    /// Frames rendered during the last second.
+    pub fn fps(&self) -> f64 {
+        self.fps_counter.fps()
+    }
+
+    /// Milliseconds between the last two rendered frames.
+    pub fn frame_time(&self) -> f64 {
+        self.fps_counter.frame_time()
+    }
+
+    pub fn scaling(&self) -> ScalingMode {
+        self.scaling
+    }
+
+    pub fn set_scaling(&mut self, scaling: ScalingMode) {
+        self.scaling = scaling;
+        self.update_transform();
+    }
+
+    /// Area of the surface the pixel buffer currently covers, in surface pixels.
+    pub fn viewport(&self) -> Viewport {
+        self.scaling.viewport(self.buffer_size(), self.size)
+    }
+
+    /// Buffer pixel under a window position such as the one reported by
+    /// `WindowEvent::CursorMoved`, `None` when it falls outside the buffer,
+    /// e.g. on a letterbox bar.
+    pub fn window_pos_to_pixel(&self, position: PhysicalPosition<f64>) -> Option<(u32, u32)> {
+        self.viewport()
+            .surface_to_buffer(self.buffer_size(), position.x, position.y)
+    }
+
+    /// Window position of the center of a buffer pixel. Pixels cropped by
+    /// `ScalingMode::Fill` map to positions outside the window.
+    pub fn pixel_to_window_pos(&self, x: u32, y: u32) -> PhysicalPosition<f64> {
+        let (x, y) = self.viewport().buffer_to_surface(self.buffer_size(), x, y);
+        PhysicalPosition::new(x, y)
+    }
+
+    // uploads the quad placement for the current scaling mode and sizes
+    fn update_transform(&self) {
+        let transform = self.viewport().clip_transform(self.size);
+        self.queue
+            .write_buffer(&self.transform_buffer, 0, bytemuck::cast_slice(&transform));
+    }
+
+    /// Current size of the surface being rendered to, in physical pixels.
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    /// Current size of the pixel buffer.
+    pub fn buffer_size(&self) -> LogicalSize<u32> {
+        LogicalSize::new(self.texture_extent.width, self.texture_extent.height)
+    }
+
+    /// Reallocates the pixel buffer and its texture at the new size.
+    /// The overlapping top left part of the old content is kept when
+    /// `Config::preserve_buffer` is set, everything else starts out black,
+    /// or palette index 0 for indexed buffers.
+    pub fn resize_buffer(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            return;
         }
+        let extent = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let mut pixels = blank_pixels(extent, self.pixel_format);
+        if self.preserve_buffer {
+            let bytes_per_pixel = self.pixel_format.bytes_per_pixel() as usize;
+            let old_row = self.texture_extent.width as usize * bytes_per_pixel;
+            let new_row = width as usize * bytes_per_pixel;
+            let copy = old_row.min(new_row);
+            let rows = self.texture_extent.height.min(height) as usize;
+            for y in 0..rows {
+                pixels[y * new_row..y * new_row + copy]
+                    .copy_from_slice(&self.pixels[y * old_row..y * old_row + copy]);
+            }
+        }
+
+        self.texture = create_pixel_texture(&self.device, extent, self.texture.format());
+        self.bind_group = create_bind_group(
+            &self.device,
+            &self.bind_group_layout,
+            &self.texture,
+            &self.sampler,
+            &self.transform_buffer,
+            &self.palette_texture,
+        );
+        self.texture_extent = extent;
+        self.pixels = pixels;
+        self.mark_all_dirty();
+        self.update_transform();
     }
 
-    // returns true if the main loop won't process the event any further
-    fn input(&mut self, event: &WindowEvent) -> bool {
-        (self.input_handler)(self, event)
+    /// Schedules a region of the pixel buffer for upload on the next render.
+    /// Only has an effect with `Config::dirty_tracking`, otherwise the whole
+    /// buffer is uploaded every frame.
+    pub fn mark_dirty(&mut self, rect: Rect) {
+        if let Some(rect) = rect.intersect(&self.buffer_rect()) {
+            merge_dirty(&mut self.dirty, rect);
+        }
     }
 
-    // software rendering
-    fn update(&mut self) {
-        (self.update_fn)(self)
+    /// The pixel buffer as a canvas. Since anything may be drawn through it
+    /// the whole buffer is marked dirty, use [`State::draw`] to only upload
+    /// what was actually drawn.
+    pub fn canvas(&mut self) -> Canvas<'_> {
+        self.mark_all_dirty();
+        Canvas::with_format_and_palette(
+            &mut self.pixels,
+            self.texture_extent.width,
+            self.texture_extent.height,
+            self.pixel_format,
+            &self.palette,
+        )
     }
 
-    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
-        let output = self.surface.get_current_texture()?; //  new surface texture we will render
-                                                          //  to
+    /// Runs `f` with the pixel buffer as a canvas and marks the region it
+    /// drew to dirty.
+    pub fn draw<R>(&mut self, f: impl FnOnce(&mut Canvas) -> R) -> R {
+        let mut canvas = Canvas::with_format_and_palette(
+            &mut self.pixels,
+            self.texture_extent.width,
+            self.texture_extent.height,
+            self.pixel_format,
+            &self.palette,
+        );
+        let result = f(&mut canvas);
+        if let Some(rect) = canvas.dirty_rect() {
+            self.mark_dirty(rect);
+        }
+        result
+    }
 
-        let mut encoder = self
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
-            }); // for creation of command buffer to be sent to gpu
+    pub fn mark_all_dirty(&mut self) {
+        self.dirty.clear();
+        self.dirty.push(self.buffer_rect());
+    }
 
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default()); // we will
-                                                                   // interact
-                                                                   // with
-                                                                   // texture
-                                                                   // actual render pass
+    fn buffer_rect(&self) -> Rect {
+        Rect::new(0, 0, self.texture_extent.width, self.texture_extent.height)
+    }
 
-        // Update the pixel buffer texture view
-        let bytes_per_row = (self.texture_extent.width as f32 * 4.0) as u32;
+    // copies one region of the pixel buffer into the texture
+    fn upload(&self, rect: Rect) {
+        let bytes_per_pixel = self.pixel_format.bytes_per_pixel();
+        let bytes_per_row = self.texture_extent.width * bytes_per_pixel;
+        let offset =
+            rect.y as u64 * bytes_per_row as u64 + (rect.x as u32 * bytes_per_pixel) as u64;
         self.queue.write_texture(
             wgpu::ImageCopyTexture {
                 texture: &self.texture,
                 mip_level: 0,
-                origin: wgpu::Origin3d { x: 0, y: 0, z: 0 },
+                origin: wgpu::Origin3d {
+                    x: rect.x as u32,
+                    y: rect.y as u32,
+                    z: 0,
+                },
                 aspect: wgpu::TextureAspect::All,
             },
             &self.pixels,
             wgpu::ImageDataLayout {
-                offset: 0,
+                offset,
                 bytes_per_row: Some(bytes_per_row),
-                rows_per_image: Some(self.texture_extent.height),
+                rows_per_image: Some(rect.height),
+            },
+            wgpu::Extent3d {
+                width: rect.width,
+                height: rect.height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    fn upload_palette(&self) {
+        self.queue.write_texture(
+            self.palette_texture.as_image_copy(),
+            &self.palette.to_bytes(),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(PALETTE_SIZE * 4),
+                rows_per_image: Some(1),
             },
-            self.texture_extent,
+            self.palette_texture.size(),
         );
+    }
+
+    pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size.width > 0 && new_size.height > 0 {
+            self.size = new_size;
+            match &mut self.target {
+                RenderTarget::Surface {
+                    surface, config, ..
+                } => {
+                    config.width = new_size.width;
+                    config.height = new_size.height;
+                    surface.configure(&self.device, config); // resize
+                }
+                RenderTarget::Offscreen { texture } => {
+                    *texture = create_offscreen_texture(&self.device, new_size, texture.format());
+                }
+            }
+            if self.buffer_follows_window {
+                self.resize_buffer(new_size.width, new_size.height);
+            } else {
+                self.update_transform();
+            }
+        }
+    }
+
+    /// Runs the app's software rendering for this frame.
+    pub fn update(&mut self, app: &mut impl App) {
+        let now = Utc::now();
+        let dt = (now - self.last_update).num_microseconds().unwrap_or(0) as f64 / 1_000_000.0;
+        self.last_update = now;
+        app.update(self, dt);
+    }
+
+    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        //  new surface texture we will render to, offscreen targets reuse their texture
+        let (output, view) = match &self.target {
+            RenderTarget::Surface { surface, .. } => {
+                let output = surface.get_current_texture()?;
+                let view = output
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                (Some(output), view)
+            }
+            RenderTarget::Offscreen { texture } => (
+                None,
+                texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            ),
+        }; // we will interact with texture actual render pass
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            }); // for creation of command buffer to be sent to gpu
+
+        // Update the pixel buffer texture, only the marked regions when tracking
+        if self.dirty_tracking {
+            for rect in std::mem::take(&mut self.dirty) {
+                self.upload(rect);
+            }
+        } else {
+            self.upload(self.buffer_rect());
+        }
+        if std::mem::take(&mut self.palette_dirty) {
+            self.upload_palette();
+        }
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
@@ -363,22 +666,257 @@ impl<'a, T> State<'a, T> {
             render_pass.set_pipeline(&self.render_pipeline);
             render_pass.set_bind_group(0, &self.bind_group, &[]);
             render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.draw(0..3, 0..1);
+            render_pass.draw(0..6, 0..1);
         } // block tells rust to drop any vars after scope
           // so we can encoder.finish()
 
         self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+        if let Some(output) = output {
+            output.present();
+        }
 
         let (frame_time, fps) = self.fps_counter.update();
-        self.window
-            .set_title(&format!("FPS: {} - Frame Time: {:.2}ms", fps, frame_time));
-        return Ok(());
+        if let (true, Some(window)) = (self.show_fps, self.window()) {
+            window.set_title(&format!(
+                "{} - FPS: {} - Frame Time: {:.2}ms",
+                self.title, fps, frame_time
+            ));
+        }
+        Ok(())
+    }
+
+    /// Copies the last rendered frame back to CPU memory as tightly packed
+    /// rows in the target format (RGBA8 sRGB for headless states).
+    /// Returns `None` when rendering to a window surface.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_frame(&self) -> Option<Vec<u8>> {
+        let RenderTarget::Offscreen { texture } = &self.target else {
+            return None;
+        };
+        let width = texture.width();
+        let height = texture.height();
+
+        // rows of a texture to buffer copy have to be 256 byte aligned
+        let unpadded_bytes_per_row = width * 4;
+        let padded_bytes_per_row = unpadded_bytes_per_row
+            .div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("pixels_readback_buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Readback Encoder"),
+            });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture.size(),
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        // block until the copy is done and the buffer is mapped
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv().ok()?.ok()?;
+
+        let mut frame = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+        {
+            let data = slice.get_mapped_range();
+            for row in data.chunks(padded_bytes_per_row as usize) {
+                frame.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+            }
+        }
+        buffer.unmap();
+        Some(frame)
     }
 }
 
+// above this many dirty regions they are merged into one upload
+const MAX_DIRTY_RECTS: usize = 16;
+
+// entries in the palette texture
+const PALETTE_SIZE: u32 = 256;
+
+// format of the texture headless states render into
+const OFFSCREEN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+#[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+fn create_instance(backends: wgpu::Backends) -> wgpu::Instance {
+    wgpu::Instance::new(wgpu::InstanceDescriptor {
+        #[cfg(not(target_arch = "wasm32"))]
+        backends,
+        #[cfg(target_arch = "wasm32")]
+        backends: wgpu::Backends::GL,
+        ..Default::default()
+    })
+}
+
+// the pixel buffer texture has to be sampled and written to on this adapter
+fn check_pixel_format(adapter: &wgpu::Adapter, pixel_format: PixelFormat) -> Result<(), Error> {
+    let usages = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST;
+    let features = adapter.get_texture_format_features(pixel_format.texture_format());
+    if features.allowed_usages.contains(usages) {
+        Ok(())
+    } else {
+        Err(Error::UnsupportedPixelFormat(pixel_format))
+    }
+}
+
+// handle for the actual graphics card
+// creates device and queue
+async fn request_device(
+    instance: &wgpu::Instance,
+    compatible_surface: Option<&wgpu::Surface<'_>>,
+) -> Result<(wgpu::Adapter, wgpu::Device, wgpu::Queue), Error> {
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            compatible_surface,
+            force_fallback_adapter: false, // do not use a software system
+        })
+        .await
+        .ok_or(Error::NoAdapter)?;
+
+    let (device, queue) = adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                required_features: wgpu::Features::empty(),
+                // webgl support
+                required_limits: if cfg!(target_arch = "wasm32") {
+                    wgpu::Limits::downlevel_webgl2_defaults()
+                } else {
+                    wgpu::Limits::default()
+                },
+                label: None,
+                memory_hints: Default::default(),
+            },
+            None,
+        )
+        .await?;
+
+    Ok((adapter, device, queue))
+}
+
+// texture the pixel buffer is uploaded to every frame
+fn create_pixel_texture(
+    device: &wgpu::Device,
+    extent: wgpu::Extent3d,
+    format: wgpu::TextureFormat,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("pixels_source_texture"),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    })
+}
+
+// one row of sRGB colors the shader resolves palette indices with
+fn create_palette_texture(device: &wgpu::Device) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("pixels_palette_texture"),
+        size: wgpu::Extent3d {
+            width: PALETTE_SIZE,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    })
+}
+
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    texture: &wgpu::Texture,
+    sampler: &wgpu::Sampler,
+    transform_buffer: &wgpu::Buffer,
+    palette_texture: &wgpu::Texture,
+) -> wgpu::BindGroup {
+    let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let palette_view = palette_texture.create_view(&wgpu::TextureViewDescriptor::default());
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("pixels_scaling_renderer_bind_group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&texture_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: transform_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::TextureView(&palette_view),
+            },
+        ],
+    })
+}
+
+// opaque black for 4 channels, RGBA or BGRA, palette index 0 when indexed
+fn blank_pixels(extent: wgpu::Extent3d, format: PixelFormat) -> Vec<u8> {
+    let pixel: &[u8] = match format {
+        PixelFormat::Indexed8 => &[0],
+        _ => &[0, 0, 0, 255],
+    };
+    pixel.repeat((extent.width * extent.height) as usize)
+}
+
+fn create_offscreen_texture(
+    device: &wgpu::Device,
+    size: PhysicalSize<u32>,
+    format: wgpu::TextureFormat,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("pixels_offscreen_texture"),
+        size: wgpu::Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen(start))]
-pub async fn run() {
+pub async fn run() -> Result<(), Error> {
     cfg_if::cfg_if! {
         if #[cfg(target_arch = "wasm32")] {
             std::panic::set_hook(Box::new(console_error_panic_hook::hook));
@@ -387,12 +925,25 @@ pub async fn run() {
             env_logger::init();
         }
     }
-    let size = LogicalSize::new(INITIAL_WINDOW.0, INITIAL_WINDOW.1);
-    let event_loop = EventLoop::new().unwrap();
+
+    let demo_app = DemoApp {
+        shape: Shape::CIRCLE,
+        color: Color::rgb(100, 150, 200),
+    };
+    run_with(demo_app, Config::default()).await
+}
+
+/// Creates a window as described by `config` and drives `app` with its
+/// event loop until the window is closed.
+pub async fn run_with<A: App>(mut app: A, config: Config) -> Result<(), Error> {
+    let size = config.window_size;
+    let event_loop = EventLoop::new()?;
     let window = WindowBuilder::new()
+        .with_title(&config.title)
         .with_inner_size(size)
-        .build(&event_loop)
-        .unwrap();
+        .with_resizable(config.resizable)
+        .with_fullscreen(config.fullscreen.then_some(Fullscreen::Borderless(None)))
+        .build(&event_loop)?;
 
     #[cfg(target_arch = "wasm32")]
     {
@@ -407,13 +958,15 @@ pub async fn run() {
                 let dst = doc.get_element_by_id("_wgpu")?;
 
                 // for fps counting in the webpage
-                let fps_counter = doc
-                    .create_element("div")
-                    .expect("Couldn't create FPS counter div");
-                fps_counter.set_id("_wgpu-fps");
-                fps_counter.set_inner_html("FPS: 0.0");
-                dst.append_child(&fps_counter)
-                    .expect("Couldn't append FPS counter to document body.");
+                if config.show_fps {
+                    let fps_counter = doc
+                        .create_element("div")
+                        .expect("Couldn't create FPS counter div");
+                    fps_counter.set_id("_wgpu-fps");
+                    fps_counter.set_inner_html("FPS: 0.0");
+                    dst.append_child(&fps_counter)
+                        .expect("Couldn't append FPS counter to document body.");
+                }
 
                 let canvas = web_sys::Element::from(window.canvas()?);
                 dst.append_child(&canvas).ok()?;
@@ -422,97 +975,129 @@ pub async fn run() {
             .expect("Couldn't append canvas to document body.");
     }
 
-    let mut demo_app = DemoApp {
-        shape: Shape::CIRCLE,
-        color: Color::new(100, 150, 200),
-    };
-    let mut state = State::new(
-        &window,
-        &mut demo_app,
-        size,
-        demo_input_handler,
-        demo_update_handler,
-    )
-    .await;
+    let window = &window;
+    let mut state = State::new(window, &config).await?;
+    app.init(&mut state);
     let mut surface_configured = false;
 
-    event_loop
-        .run(move |event, control_flow| {
-            #[cfg(target_arch = "wasm32")]
-            {
-                web_sys::window()
-                    .and_then(|win| win.document())
-                    .and_then(|doc| {
-                        let fps_counter_element = doc
-                            .get_element_by_id("_wgpu-fps")
-                            .expect("Couldn't find FPS counter element");
-                        fps_counter_element
-                            .set_inner_html(&format!("FPS: {:.2}", state.fps_counter.fps()));
-                        Some(())
-                    })
-                    .expect("Couldn't update FPS counter in document body.");
-            }
-            match event {
-                Event::WindowEvent {
-                    ref event,
-                    window_id,
-                } if window_id == state.window().id() => {
-                    if !state.input(event) {
-                        match event {
-                            WindowEvent::CloseRequested
-                            | WindowEvent::KeyboardInput {
-                                event:
-                                    KeyEvent {
-                                        state: ElementState::Pressed,
-                                        physical_key: PhysicalKey::Code(KeyCode::Escape),
-                                        ..
-                                    },
+    event_loop.run(move |event, control_flow| {
+        #[cfg(target_arch = "wasm32")]
+        if state.show_fps {
+            web_sys::window()
+                .and_then(|win| win.document())
+                .and_then(|doc| {
+                    let fps_counter_element = doc
+                        .get_element_by_id("_wgpu-fps")
+                        .expect("Couldn't find FPS counter element");
+                    fps_counter_element
+                        .set_inner_html(&format!("FPS: {:.2}", state.fps_counter.fps()));
+                    Some(())
+                })
+                .expect("Couldn't update FPS counter in document body.");
+        }
+        match event {
+            Event::WindowEvent {
+                ref event,
+                window_id,
+            } if window_id == window.id() && !app.input(&mut state, event) => {
+                match event {
+                    WindowEvent::CloseRequested
+                    | WindowEvent::KeyboardInput {
+                        event:
+                            KeyEvent {
+                                state: ElementState::Pressed,
+                                physical_key: PhysicalKey::Code(KeyCode::Escape),
                                 ..
-                            } => control_flow.exit(),
-                            WindowEvent::Resized(physical_size) => {
-                                log::info!("physical_size: {physical_size:?}");
-                                surface_configured = true;
-                                state.resize(physical_size.to_logical(1.0));
+                            },
+                        ..
+                    } => control_flow.exit(),
+                    WindowEvent::Resized(physical_size) => {
+                        log::info!("physical_size: {physical_size:?}");
+                        surface_configured = true;
+                        state.resize(*physical_size);
+                        let size = state.size();
+                        app.resize(&mut state, size);
+                    }
+                    WindowEvent::Focused(focused) => app.focus(&mut state, *focused),
+                    WindowEvent::RedrawRequested => {
+                        // This tells winit that we want another frame after this one
+                        window.request_redraw();
+
+                        if !surface_configured {
+                            return;
+                        }
+
+                        state.update(&mut app);
+                        match state.render() {
+                            Ok(_) => {}
+                            // Reconfigure the surface if it's lost or outdated
+                            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                                state.resize(state.size)
+                            }
+                            // The system is out of memory, we should probably quit
+                            Err(wgpu::SurfaceError::OutOfMemory) => {
+                                log::error!("OutOfMemory");
+                                control_flow.exit();
                             }
-                            WindowEvent::RedrawRequested => {
-                                // This tells winit that we want another frame after this one
-                                state.window().request_redraw();
-
-                                if !surface_configured {
-                                    return;
-                                }
-
-                                state.update();
-                                match state.render() {
-                                    Ok(_) => {}
-                                    // Reconfigure the surface if it's lost or outdated
-                                    Err(
-                                        wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated,
-                                    ) => state.resize(state.size),
-                                    // The system is out of memory, we should probably quit
-                                    Err(wgpu::SurfaceError::OutOfMemory) => {
-                                        log::error!("OutOfMemory");
-                                        control_flow.exit();
-                                    }
-
-                                    // This happens when the a frame takes too long to present
-                                    Err(wgpu::SurfaceError::Timeout) => {
-                                        log::warn!("Surface timeout")
-                                    }
-                                }
+
+                            // This happens when the a frame takes too long to present
+                            Err(wgpu::SurfaceError::Timeout) => {
+                                log::warn!("Surface timeout")
                             }
-                            _ => {}
                         }
                     }
+                    _ => {}
                 }
-                _ => {}
             }
-        })
-        .unwrap();
+            Event::Suspended => app.suspend(&mut state),
+            Event::Resumed => app.resume(&mut state),
+            Event::LoopExiting => app.exit(&mut state),
+            _ => {}
+        }
+    })?;
+    Ok(())
+}
+
+// adds `rect` to regions that don't overlap each other, merging it with the
+// ones it overlaps so nothing is uploaded twice. Growing can make it reach
+// regions it missed before, so this repeats until none overlap.
+fn merge_dirty(dirty: &mut Vec<Rect>, mut rect: Rect) {
+    while let Some(i) = dirty
+        .iter()
+        .position(|other| other.intersect(&rect).is_some())
+    {
+        rect = rect.union(&dirty.swap_remove(i));
+    }
+    if dirty.len() >= MAX_DIRTY_RECTS {
+        // past this point one bigger upload beats many small ones
+        rect = dirty.drain(..).fold(rect, |acc, other| acc.union(&other));
+    }
+    dirty.push(rect);
 }
 
-// fn default_update(_state: &mut State<DemoApp>) {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merged_dirty_regions_never_overlap() {
+        let mut dirty = vec![Rect::new(1, 3, 1, 1), Rect::new(0, 0, 1, 5)];
+        merge_dirty(&mut dirty, Rect::new(0, 0, 2, 1));
+        assert_eq!(dirty, [Rect::new(0, 0, 2, 5)]);
+
+        let mut dirty = vec![Rect::new(4, 4, 2, 2)];
+        merge_dirty(&mut dirty, Rect::new(0, 0, 2, 2));
+        assert_eq!(dirty, [Rect::new(4, 4, 2, 2), Rect::new(0, 0, 2, 2)]);
+    }
 
-// fn default_input_handler(_state: &mut State<DemoApp>, _event: &WindowEvent) -> bool {
-//     false
-// }
+    #[test]
+    fn too_many_dirty_regions_become_one() {
+        let mut dirty = Vec::new();
+        for i in 0..MAX_DIRTY_RECTS as i32 {
+            merge_dirty(&mut dirty, Rect::new(i * 2, 0, 1, 1));
+        }
+        assert_eq!(dirty.len(), MAX_DIRTY_RECTS);
+        merge_dirty(&mut dirty, Rect::new(0, 9, 1, 1));
+        assert_eq!(dirty, [Rect::new(0, 0, MAX_DIRTY_RECTS as u32 * 2 - 1, 10)]);
+    }
+}
@@ -1,4 +1,25 @@
-pub mod init;
-pub mod fps;
-pub mod draw;
+pub mod app;
+pub mod bezier;
+pub mod blend;
+pub mod canvas;
+pub mod color;
+pub mod config;
+mod coverage;
 pub mod demo;
+pub mod dither;
+pub mod draw;
+pub mod ellipse;
+pub mod error;
+pub mod fill;
+pub mod format;
+pub mod fps;
+pub mod gradient;
+pub mod init;
+pub mod line;
+pub mod palette;
+pub mod polygon;
+pub mod rect;
+pub mod scaling;
+pub mod sprite;
+pub mod text;
+pub mod transform;
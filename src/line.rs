@@ -0,0 +1,346 @@
+use crate::{canvas::Canvas, color::Color, coverage::to_coverage, polygon::FillRule};
+
+impl Canvas<'_> {
+    /// One pixel wide line between two pixels, both ends included. Drawn with
+    /// Xiaolin Wu's algorithm when anti-aliasing is on.
+    pub fn line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+        let transform = self.transform();
+        let (x0, y0) = transform.apply_pixel(x0, y0);
+        let (x1, y1) = transform.apply_pixel(x1, y1);
+        self.plot_line(x0, y0, x1, y1, color, false);
+    }
+
+    /// Horizontal line from `x0` to `x1` inclusive.
+    pub fn hline(&mut self, x0: i32, x1: i32, y: i32, color: Color) {
+        self.line(x0, y, x1, y, color);
+    }
+
+    /// Vertical line from `y0` to `y1` inclusive.
+    pub fn vline(&mut self, x: i32, y0: i32, y1: i32, color: Color) {
+        self.line(x, y0, x, y1, color);
+    }
+
+    /// Line `width` pixels wide with flat ends, centered on the given points.
+    pub fn thick_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, width: u32, color: Color) {
+        let transform = self.transform();
+        let width = width as f32 * transform.length_scale();
+        if width <= 1.0 {
+            self.line(x0, y0, x1, y1, color);
+            return;
+        }
+        let from = transform.apply(x0 as f32 + 0.5, y0 as f32 + 0.5);
+        let to = transform.apply(x1 as f32 + 0.5, y1 as f32 + 0.5);
+        self.device_thick_line(from, to, width, color);
+    }
+
+    /// Connected line segments through `points`, back to the first point
+    /// when `closed` is set. Shared points are only drawn once.
+    pub fn polyline(&mut self, points: &[(i32, i32)], closed: bool, color: Color) {
+        let transform = self.transform();
+        let points: Vec<_> = points
+            .iter()
+            .map(|&(x, y)| transform.apply_pixel(x, y))
+            .collect();
+        self.device_polyline(&points, closed, color);
+    }
+
+    /// Polyline `width` pixels wide, the joints are rounded.
+    pub fn thick_polyline(
+        &mut self,
+        points: &[(i32, i32)],
+        closed: bool,
+        width: u32,
+        color: Color,
+    ) {
+        let transform = self.transform();
+        let width = width as f32 * transform.length_scale();
+        if width <= 1.0 {
+            self.polyline(points, closed, color);
+            return;
+        }
+        let points: Vec<_> = points
+            .iter()
+            .map(|&(x, y)| transform.apply(x as f32 + 0.5, y as f32 + 0.5))
+            .collect();
+        self.device_thick_polyline(&points, closed, width, color);
+    }
+
+    pub(crate) fn device_hline(&mut self, x0: i32, x1: i32, y: i32, color: Color) {
+        let (left, right) = (x0.min(x1), x0.max(x1));
+        let clip = self.clip();
+        if y < clip.y || y >= clip.bottom() {
+            return;
+        }
+        let end = right.saturating_add(1).min(clip.right());
+        self.write_span(y, left.max(clip.x), end, color);
+    }
+
+    pub(crate) fn device_vline(&mut self, x: i32, y0: i32, y1: i32, color: Color) {
+        let (top, bottom) = (y0.min(y1), y0.max(y1));
+        let clip = self.clip();
+        if x < clip.x || x >= clip.right() {
+            return;
+        }
+        for y in top.max(clip.y)..bottom.saturating_add(1).min(clip.bottom()) {
+            self.device_pixel(x, y, color);
+        }
+    }
+
+    // quad between two points given in continuous canvas coordinates
+    pub(crate) fn device_thick_line(
+        &mut self,
+        from: (f32, f32),
+        to: (f32, f32),
+        width: f32,
+        color: Color,
+    ) {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length = (dx * dx + dy * dy).sqrt();
+        let half = width / 2.0;
+        // a square around a single point
+        let (nx, ny, ex, ey) = if length == 0.0 {
+            (0.0, half, half, 0.0)
+        } else {
+            (-dy / length * half, dx / length * half, 0.0, 0.0)
+        };
+        let (ax, ay) = (from.0 - ex, from.1 - ey);
+        let (bx, by) = (to.0 + ex, to.1 + ey);
+        self.device_polygon(
+            &[
+                (ax + nx, ay + ny),
+                (bx + nx, by + ny),
+                (bx - nx, by - ny),
+                (ax - nx, ay - ny),
+            ],
+            FillRule::NonZero,
+            color,
+        );
+    }
+
+    // points in continuous canvas coordinates, rounded joints
+    pub(crate) fn device_thick_polyline(
+        &mut self,
+        points: &[(f32, f32)],
+        closed: bool,
+        width: f32,
+        color: Color,
+    ) {
+        let mut segments: Vec<_> = points.windows(2).map(|s| (s[0], s[1])).collect();
+        if closed && points.len() > 2 {
+            segments.push((points[points.len() - 1], points[0]));
+        }
+        for (from, to) in segments {
+            self.device_thick_line(from, to, width, color);
+        }
+        // fill the wedges between segments
+        let joints = if closed {
+            points
+        } else if points.len() > 2 {
+            &points[1..points.len() - 1]
+        } else {
+            &[]
+        };
+        let radius = (width / 2.0) as u32;
+        for &(x, y) in joints {
+            self.device_fill_ellipse(x.floor() as i32, y.floor() as i32, radius, radius, color);
+        }
+    }
+
+    pub(crate) fn device_polyline(&mut self, points: &[(i32, i32)], closed: bool, color: Color) {
+        match points {
+            [] => {}
+            [(x, y)] => self.device_pixel(*x, *y, color),
+            _ => {
+                for segment in points.windows(2) {
+                    let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+                    self.plot_line(x0, y0, x1, y1, color, true);
+                }
+                let (first, last) = (points[0], points[points.len() - 1]);
+                if closed {
+                    self.plot_line(last.0, last.1, first.0, first.1, color, true);
+                } else {
+                    self.device_pixel(last.0, last.1, color);
+                }
+            }
+        }
+    }
+
+    // integer Bresenham, clipped by stepping only through the visible part
+    pub(crate) fn plot_line(
+        &mut self,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        color: Color,
+        skip_last: bool,
+    ) {
+        if y0 == y1 && !skip_last {
+            self.device_hline(x0, x1, y0, color);
+            return;
+        }
+        if x0 == x1 && !skip_last {
+            self.device_vline(x0, y0, y1, color);
+            return;
+        }
+        if self.antialias() {
+            self.wu_line(x0, y0, x1, y1, color, skip_last);
+            return;
+        }
+
+        let clip = self.clip();
+        // endpoints can be the whole i32 range apart
+        let (x0, y0, x1, y1) = (x0 as i64, y0 as i64, x1 as i64, y1 as i64);
+        let (dx, dy) = ((x1 - x0).abs(), (y1 - y0).abs());
+        let (sx, sy) = ((x1 - x0).signum(), (y1 - y0).signum());
+        // walk along the longer axis, the shorter one follows
+        let (major, minor) = (dx.max(dy), dx.min(dy));
+        let steps = if skip_last { major - 1 } else { major };
+        let point = |i: i64| -> (i64, i64) {
+            // same rounding as the error term of the incremental algorithm
+            let offset =
+                (2 * i as i128 * minor as i128 + major as i128) / (2 * major.max(1) as i128);
+            let offset = offset as i64;
+            if dx >= dy {
+                (x0 + sx * i, y0 + sy * offset)
+            } else {
+                (x0 + sx * offset, y0 + sy * i)
+            }
+        };
+
+        // steps along the major axis that stay inside the clip rectangle
+        let (major_start, major_sign, major_min, major_max) = if dx >= dy {
+            (x0, sx, clip.x, clip.right() - 1)
+        } else {
+            (y0, sy, clip.y, clip.bottom() - 1)
+        };
+        let (first, last) = axis_range(major_start, major_sign, major_min, major_max);
+        let first = first.max(0);
+        let last = last.min(steps);
+
+        for i in first..=last {
+            // the minor axis may still be far outside
+            let (x, y) = point(i);
+            if let (Ok(x), Ok(y)) = (i32::try_from(x), i32::try_from(y)) {
+                self.device_pixel(x, y, color);
+            }
+        }
+    }
+
+    // Xiaolin Wu's line through pixel centers, every step along the major axis
+    // splits its coverage between the two nearest pixels on the minor one
+    fn wu_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color, skip_last: bool) {
+        let (x0, y0, x1, y1) = (x0 as i64, y0 as i64, x1 as i64, y1 as i64);
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        let (major0, minor0, major1, minor1) = if steep {
+            (y0, x0, y1, x1)
+        } else {
+            (x0, y0, x1, y1)
+        };
+        let steps = (major1 - major0).abs();
+        if steps == 0 {
+            return;
+        }
+        let sign = (major1 - major0).signum();
+        let gradient = (minor1 - minor0) as f64 / steps as f64;
+
+        let clip = self.clip();
+        let (major_min, major_max) = if steep {
+            (clip.y, clip.bottom() - 1)
+        } else {
+            (clip.x, clip.right() - 1)
+        };
+        let (first, last) = axis_range(major0, sign, major_min, major_max);
+        let last = last.min(if skip_last { steps - 1 } else { steps });
+
+        for i in first.max(0)..=last {
+            // inside the clip along the major axis
+            let major = (major0 + sign * i) as i32;
+            let minor = minor0 as f64 + gradient * i as f64;
+            let base = minor.floor();
+            let fraction = (minor - base) as f32;
+            for (offset, coverage) in [(0, 1.0 - fraction), (1, fraction)] {
+                // saturates far outside the clip, where nothing is drawn
+                let minor = (base as i32).saturating_add(offset);
+                let (x, y) = if steep {
+                    (minor, major)
+                } else {
+                    (major, minor)
+                };
+                self.blend_pixel(x, y, color, to_coverage(coverage));
+            }
+        }
+    }
+}
+
+// steps i >= 0 for which start + sign * i lies in min..=max, may be empty
+fn axis_range(start: i64, sign: i64, min: i32, max: i32) -> (i64, i64) {
+    let (min, max) = (min as i64, max as i64);
+    if sign >= 0 {
+        (min - start, max - start)
+    } else {
+        (start - max, start - min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        canvas::{
+            tests::{buffer, painted},
+            Canvas,
+        },
+        color::Color,
+        format::PixelFormat,
+        transform::Transform,
+    };
+
+    const RED: Color = Color::rgb(255, 0, 0);
+    const BLUE: Color = Color::rgb(0, 0, 255);
+
+    #[test]
+    fn bresenham_steps_along_the_longer_axis() {
+        let mut pixels = buffer(5, 3, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 5, 3);
+        canvas.line(0, 0, 4, 2, RED);
+        assert_eq!(
+            painted(&canvas, RED),
+            [(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]
+        );
+    }
+
+    #[test]
+    fn closed_polylines_draw_shared_points_once() {
+        let mut pixels = buffer(3, 3, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 3, 3);
+        let translucent = Color::rgba(255, 0, 0, 128);
+        canvas.polyline(&[(0, 0), (2, 0), (2, 2), (0, 2)], true, translucent);
+        let once = canvas.get_pixel(0, 0);
+        assert!(once.is_some_and(|color| color.a < 255));
+        for (x, y) in [(2, 0), (2, 2), (0, 2), (1, 0), (2, 1)] {
+            assert_eq!(canvas.get_pixel(x, y), once);
+        }
+        assert_eq!(canvas.get_pixel(1, 1), Some(Color::rgba(0, 0, 0, 0)));
+    }
+
+    #[test]
+    fn lines_far_outside_do_not_overflow() {
+        let mut pixels = buffer(4, 4, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 4, 4);
+        canvas.line(i32::MIN, 1, i32::MAX, 1, RED);
+        canvas.line(i32::MAX, i32::MIN, i32::MAX, i32::MAX, RED);
+        assert_eq!(painted(&canvas, RED), [(0, 1), (1, 1), (2, 1), (3, 1)]);
+
+        for antialias in [false, true] {
+            let mut pixels = buffer(4, 4, PixelFormat::Rgba8Srgb);
+            let mut canvas = Canvas::new(&mut pixels, 4, 4);
+            canvas.set_antialias(antialias);
+            canvas.line(i32::MIN, 2, i32::MAX, 3, RED);
+            canvas.line(i32::MIN, i32::MIN, i32::MAX, i32::MAX, RED);
+            canvas.with_transform(Transform::scale(1e10, 1.0), |canvas| {
+                canvas.line(-1, 0, 1, 3, BLUE);
+            });
+            assert!(!painted(&canvas, RED).is_empty());
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use w_pixbuf::init::*;
 
 fn main() {
-    pollster::block_on(run());
+    if let Err(err) = pollster::block_on(run()) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,155 @@
+use std::ops::RangeInclusive;
+
+use crate::color::Color;
+
+/// The 256 colors pixels of a [`PixelFormat::Indexed8`] buffer refer to.
+///
+/// The palette is uploaded to the GPU apart from the pixels, so changing or
+/// rotating entries recolors the whole screen without redrawing anything.
+///
+/// [`PixelFormat::Indexed8`]: crate::format::PixelFormat::Indexed8
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    colors: [Color; 256],
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::grayscale()
+    }
+}
+
+impl Palette {
+    /// Index `i` is the gray `i`, from black at 0 to white at 255.
+    pub const fn grayscale() -> Self {
+        let mut colors = [Color::BLACK; 256];
+        let mut i = 0;
+        while i < 256 {
+            colors[i] = Color::rgb(i as u8, i as u8, i as u8);
+            i += 1;
+        }
+        Self { colors }
+    }
+
+    /// Takes up to 256 colors, the entries after them are opaque black.
+    pub fn from_colors(colors: &[Color]) -> Self {
+        let mut palette = Self {
+            colors: [Color::BLACK; 256],
+        };
+        for (entry, &color) in palette.colors.iter_mut().zip(colors) {
+            *entry = color;
+        }
+        palette
+    }
+
+    pub fn get(&self, index: u8) -> Color {
+        self.colors[index as usize]
+    }
+
+    pub fn set(&mut self, index: u8, color: Color) {
+        self.colors[index as usize] = color;
+    }
+
+    /// All 256 entries, e.g. to dither to with [`Canvas::dither`].
+    ///
+    /// [`Canvas::dither`]: crate::canvas::Canvas::dither
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    /// Shifts the entries in `range` by `steps` towards higher indices,
+    /// wrapping around inside the range. Calling this every few frames gives
+    /// the classic palette cycling effects such as flowing water or fire,
+    /// negative steps run the other way.
+    pub fn rotate(&mut self, range: RangeInclusive<u8>, steps: i32) {
+        let (start, end) = (*range.start() as usize, *range.end() as usize);
+        if start >= end {
+            return;
+        }
+        let entries = &mut self.colors[start..=end];
+        let steps = steps.rem_euclid(entries.len() as i32) as usize;
+        entries.rotate_right(steps);
+    }
+
+    /// Index of the entry closest to `color`, the first one on exact matches.
+    pub fn nearest(&self, color: Color) -> u8 {
+        if let Some(index) = self.colors.iter().position(|&entry| entry == color) {
+            return index as u8;
+        }
+        let distance = |entry: &Color| {
+            let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+            d(entry.r, color.r) + d(entry.g, color.g) + d(entry.b, color.b) + d(entry.a, color.a)
+        };
+        self.colors
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, entry)| distance(entry))
+            .map_or(0, |(index, _)| index as u8)
+    }
+
+    // entries as sRGB RGBA bytes for the palette texture
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        self.colors
+            .iter()
+            .flat_map(|color| [color.r, color.g, color.b, color.a])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        canvas::{tests::buffer, Canvas},
+        format::PixelFormat,
+    };
+
+    const RED: Color = Color::rgb(255, 0, 0);
+    const BLUE: Color = Color::rgb(0, 0, 255);
+
+    #[test]
+    fn nearest_prefers_exact_matches() {
+        let palette = Palette::from_colors(&[Color::BLACK, RED, BLUE, RED]);
+        assert_eq!(palette.nearest(RED), 1);
+        assert_eq!(palette.nearest(Color::rgb(10, 0, 200)), 2);
+        assert_eq!(Palette::grayscale().nearest(Color::rgb(100, 102, 104)), 102);
+    }
+
+    #[test]
+    fn rotate_wraps_inside_the_range() {
+        let mut palette = Palette::grayscale();
+        palette.rotate(1..=3, 1);
+        let grays: Vec<_> = (0..5).map(|i| palette.get(i).r).collect();
+        assert_eq!(grays, [0, 3, 1, 2, 4]);
+        palette.rotate(1..=3, -4);
+        let grays: Vec<_> = (0..5).map(|i| palette.get(i).r).collect();
+        assert_eq!(grays, [0, 1, 2, 3, 4]);
+        palette.rotate(2..=2, 5);
+        assert_eq!(palette, Palette::grayscale());
+    }
+
+    #[test]
+    fn indexed_stores_nearest_palette_entry() {
+        let palette = Palette::from_colors(&[Color::BLACK, RED, BLUE]);
+        let mut pixels = buffer(3, 1, PixelFormat::Indexed8);
+        let mut canvas = Canvas::with_palette(&mut pixels, 3, 1, &palette);
+        canvas.set_pixel(0, 0, BLUE);
+        canvas.set_pixel(1, 0, Color::rgb(240, 20, 10));
+        assert_eq!(canvas.get_pixel(0, 0), Some(BLUE));
+        assert_eq!(canvas.get_pixel(1, 0), Some(RED));
+        assert_eq!(canvas.get_pixel(2, 0), Some(Color::BLACK));
+        assert_eq!(pixels, [2, 1, 0]);
+    }
+
+    #[test]
+    fn indexed_runs_of_one_color_reuse_the_lookup() {
+        let palette = Palette::from_colors(&[Color::BLACK, RED, BLUE]);
+        let mut pixels = buffer(4, 1, PixelFormat::Indexed8);
+        let mut canvas = Canvas::with_palette(&mut pixels, 4, 1, &palette);
+        canvas.set_pixel(0, 0, BLUE);
+        canvas.set_pixel(1, 0, BLUE);
+        canvas.set_pixel(2, 0, Color::rgb(200, 0, 0));
+        canvas.set_pixel(3, 0, BLUE);
+        assert_eq!(pixels, [2, 2, 1, 2]);
+    }
+}
@@ -0,0 +1,285 @@
+use crate::{
+    canvas::Canvas,
+    color::Color,
+    coverage::{sample_rows, CoverageRow},
+    gradient::Paint,
+};
+
+/// Decides which parts of a self intersecting polygon count as inside.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillRule {
+    /// Inside where a ray crosses an odd number of edges.
+    #[default]
+    EvenOdd,
+    /// Inside where the edges wind around the point at least once.
+    NonZero,
+}
+
+// polygon edge, oriented top to bottom
+struct Edge {
+    top: f32,
+    bottom: f32,
+    x_at_top: f32,
+    slope: f32,
+    winding: i32,
+}
+
+// walks the edges of a polygon down the rows, scanlines have to be visited
+// from top to bottom
+struct Scanner {
+    edges: Vec<Edge>,
+    next_edge: usize,
+    active: Vec<usize>,
+    crossings: Vec<(f32, i32)>,
+}
+
+impl Scanner {
+    // every contour is closed back to its first point
+    fn new(contours: &[&[(f32, f32)]]) -> Option<Self> {
+        let mut edges: Vec<Edge> = contours
+            .iter()
+            .filter(|points| points.len() >= 3)
+            .flat_map(|points| points.iter().zip(points.iter().cycle().skip(1)))
+            .filter(|(a, b)| a.1 != b.1) // horizontal edges never cross a scanline
+            .map(|(&(ax, ay), &(bx, by))| {
+                let (top, bottom, winding) = if ay < by {
+                    ((ax, ay), (bx, by), 1)
+                } else {
+                    ((bx, by), (ax, ay), -1)
+                };
+                Edge {
+                    top: top.1,
+                    bottom: bottom.1,
+                    x_at_top: top.0,
+                    slope: (bottom.0 - top.0) / (bottom.1 - top.1),
+                    winding,
+                }
+            })
+            .collect();
+        if edges.is_empty() {
+            return None;
+        }
+        edges.sort_by(|a, b| a.top.total_cmp(&b.top));
+        Some(Self {
+            edges,
+            next_edge: 0,
+            active: Vec::new(),
+            crossings: Vec::new(),
+        })
+    }
+
+    // top and bottom of the polygon
+    fn extent(&self) -> (f32, f32) {
+        let bottom = self
+            .edges
+            .iter()
+            .map(|e| e.bottom)
+            .fold(f32::NEG_INFINITY, f32::max);
+        (self.edges[0].top, bottom)
+    }
+
+    // inside spans [x0, x1) of the scanline at height y
+    fn spans(&mut self, y: f32, rule: FillRule, spans: &mut Vec<(f32, f32)>) {
+        while self.next_edge < self.edges.len() && self.edges[self.next_edge].top <= y {
+            self.active.push(self.next_edge);
+            self.next_edge += 1;
+        }
+        let edges = &self.edges;
+        self.active.retain(|&e| edges[e].bottom > y);
+
+        self.crossings.clear();
+        self.crossings.extend(self.active.iter().map(|&e| {
+            let edge = &edges[e];
+            (edge.x_at_top + (y - edge.top) * edge.slope, edge.winding)
+        }));
+        self.crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        spans.clear();
+        let mut winding = 0;
+        for (i, &(x, edge_winding)) in self.crossings.iter().enumerate() {
+            let inside = match rule {
+                FillRule::EvenOdd => i % 2 == 0,
+                FillRule::NonZero => {
+                    winding += edge_winding;
+                    winding != 0
+                }
+            };
+            if !inside {
+                continue;
+            }
+            let Some(&(next_x, _)) = self.crossings.get(i + 1) else {
+                break;
+            };
+            spans.push((x, next_x));
+        }
+    }
+}
+
+impl Canvas<'_> {
+    pub fn fill_triangle(
+        &mut self,
+        v0: (f32, f32),
+        v1: (f32, f32),
+        v2: (f32, f32),
+        paint: impl Into<Paint>,
+    ) {
+        self.fill_polygon(&[v0, v1, v2], FillRule::NonZero, paint);
+    }
+
+    /// Fills the polygon through `points`, closed back to the first one.
+    ///
+    /// A pixel is covered when its center is inside. Centers exactly on an
+    /// edge belong to the polygon on its top and left edges only, so polygons
+    /// sharing an edge never draw the same pixel twice. With anti-aliasing
+    /// each pixel is blended by the area the polygon covers instead.
+    pub fn fill_polygon(&mut self, points: &[(f32, f32)], rule: FillRule, paint: impl Into<Paint>) {
+        let transform = self.transform();
+        self.with_paint(paint.into(), |canvas, color| {
+            if transform.is_identity() {
+                canvas.device_polygon(points, rule, color);
+            } else {
+                let points: Vec<_> = points.iter().map(|&(x, y)| transform.apply(x, y)).collect();
+                canvas.device_polygon(&points, rule, color);
+            }
+        });
+    }
+
+    pub(crate) fn device_polygon(&mut self, points: &[(f32, f32)], rule: FillRule, color: Color) {
+        self.device_contours(&[points], rule, color);
+    }
+
+    // fills several closed contours as one shape, so inner ones can cut holes
+    pub(crate) fn device_contours(
+        &mut self,
+        contours: &[&[(f32, f32)]],
+        rule: FillRule,
+        color: Color,
+    ) {
+        let Some(mut scanner) = Scanner::new(contours) else {
+            return;
+        };
+        let clip = self.clip();
+        let (top, bottom) = scanner.extent();
+        let mut spans = Vec::new();
+
+        if self.antialias() {
+            let (left, right) = contours
+                .iter()
+                .flat_map(|points| points.iter())
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(l, r), p| {
+                    (l.min(p.0), r.max(p.0))
+                });
+            let left = (left.floor() as i32).max(clip.x);
+            let right = (right.ceil() as i32).min(clip.right());
+            let mut row = CoverageRow::new(left, right);
+            // every row the outline passes through
+            let first_row = (top.floor() as i32).max(clip.y);
+            let last_row = (bottom.ceil() as i32).min(clip.bottom());
+            for y in first_row..last_row {
+                row.clear();
+                for sample in sample_rows(y) {
+                    scanner.spans(sample, rule, &mut spans);
+                    for &(x0, x1) in &spans {
+                        row.add_span(x0, x1);
+                    }
+                }
+                self.blend_row(y, &row, color);
+            }
+            return;
+        }
+
+        // rows whose centers lie in [top, bottom)
+        let first_row = ((top - 0.5).ceil() as i32).max(clip.y);
+        let last_row = ((bottom - 0.5).ceil() as i32).min(clip.bottom());
+        for y in first_row..last_row {
+            scanner.spans(y as f32 + 0.5, rule, &mut spans);
+            for &(x, next_x) in &spans {
+                // centers in [x, next_x)
+                let x0 = ((x - 0.5).ceil() as i32).max(clip.x);
+                let x1 = ((next_x - 0.5).ceil() as i32).min(clip.right());
+                self.write_span(y, x0, x1, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        blend::BlendMode,
+        canvas::tests::{buffer, painted},
+        format::PixelFormat,
+    };
+
+    const RED: Color = Color::rgb(255, 0, 0);
+
+    // two squares overlapping in (2, 2)..(4, 4), both wound clockwise
+    const OVERLAPPING: [(f32, f32); 8] = [
+        (0.0, 0.0),
+        (4.0, 0.0),
+        (4.0, 4.0),
+        (2.0, 4.0),
+        (2.0, 2.0),
+        (6.0, 2.0),
+        (6.0, 6.0),
+        (0.0, 6.0),
+    ];
+
+    fn fill(points: &[(f32, f32)], rule: FillRule) -> Vec<(i32, i32)> {
+        let mut pixels = buffer(8, 8, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 8, 8);
+        canvas.fill_polygon(points, rule, RED);
+        painted(&canvas, RED)
+    }
+
+    #[test]
+    fn fill_rules_decide_overlaps() {
+        let even_odd = fill(&OVERLAPPING, FillRule::EvenOdd);
+        let non_zero = fill(&OVERLAPPING, FillRule::NonZero);
+        assert!(!even_odd.contains(&(3, 3)));
+        assert!(non_zero.contains(&(3, 3)));
+        assert_eq!(non_zero.len(), 36 - 4);
+        assert_eq!(even_odd.len(), non_zero.len() - 4);
+    }
+
+    #[test]
+    fn pixel_centers_on_edges_follow_the_top_left_rule() {
+        let square = fill(
+            &[(1.5, 1.5), (3.5, 1.5), (3.5, 3.5), (1.5, 3.5)],
+            FillRule::EvenOdd,
+        );
+        assert_eq!(square, [(1, 1), (2, 1), (1, 2), (2, 2)]);
+
+        // two triangles sharing a diagonal touch every pixel exactly once
+        let mut pixels = buffer(8, 8, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 8, 8);
+        canvas.set_blend_mode(BlendMode::Add);
+        let step = Color::rgb(100, 0, 0);
+        canvas.fill_triangle((0.0, 0.0), (8.0, 0.0), (8.0, 8.0), step);
+        canvas.fill_triangle((0.0, 0.0), (8.0, 8.0), (0.0, 8.0), step);
+        assert_eq!(painted(&canvas, Color::rgb(100, 0, 0)).len(), 64);
+    }
+
+    #[test]
+    fn degenerate_polygons_draw_nothing() {
+        assert!(fill(&[(1.0, 1.0), (5.0, 5.0)], FillRule::NonZero).is_empty());
+        assert!(fill(&[(1.0, 1.0), (5.0, 1.0), (3.0, 1.0)], FillRule::NonZero).is_empty());
+        assert!(fill(&[(0.0, 0.0), (4.0, 4.0), (8.0, 8.0)], FillRule::NonZero).is_empty());
+    }
+
+    #[test]
+    fn antialiased_edges_are_blended_by_area() {
+        let mut pixels = buffer(4, 1, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 4, 1);
+        canvas.clear(Color::BLACK);
+        canvas.set_antialias(true);
+        canvas.fill_polygon(
+            &[(0.5, 0.0), (2.25, 0.0), (2.25, 1.0), (0.5, 1.0)],
+            FillRule::NonZero,
+            Color::rgb(255, 255, 255),
+        );
+        let reds: Vec<_> = (0..4).map(|x| canvas.get_pixel(x, 0).unwrap().r).collect();
+        assert_eq!(reds, [128, 255, 64, 0]);
+    }
+}
@@ -0,0 +1,104 @@
+/// Axis aligned rectangle in buffer pixels. The origin may be negative so
+/// shapes partly outside the buffer can be described before clipping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Smallest rectangle containing both corners, inclusive. Corners
+    /// spanning the whole `i32` range lose their last column or row.
+    pub fn from_corners(x0: i32, y0: i32, x1: i32, y1: i32) -> Self {
+        let (left, right) = (x0.min(x1), x0.max(x1));
+        let (top, bottom) = (y0.min(y1), y0.max(y1));
+        Self::new(
+            left,
+            top,
+            span(left, right as i64 + 1),
+            span(top, bottom as i64 + 1),
+        )
+    }
+
+    /// One past the last column, saturating at `i32::MAX`.
+    pub fn right(&self) -> i32 {
+        saturate(self.x as i64 + self.width as i64)
+    }
+
+    /// One past the last row, saturating at `i32::MAX`.
+    pub fn bottom(&self) -> i32 {
+        saturate(self.y as i64 + self.height as i64)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    /// Overlapping part of both rectangles, `None` if they don't overlap.
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        (left < right && top < bottom)
+            .then(|| Rect::new(left, top, span(left, right), span(top, bottom)))
+    }
+
+    /// Smallest rectangle containing both.
+    pub fn union(&self, other: &Rect) -> Rect {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        let left = self.x.min(other.x);
+        let top = self.y.min(other.y);
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+        Rect::new(left, top, span(left, right), span(top, bottom))
+    }
+}
+
+fn saturate(value: i64) -> i32 {
+    value.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+}
+
+// length of start..end as a size, which can exceed i32::MAX
+pub(crate) fn span(start: i32, end: impl Into<i64>) -> u32 {
+    (end.into() - start as i64).clamp(0, u32::MAX as i64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rect_corners_spanning_the_whole_range() {
+        let rect = Rect::from_corners(i32::MAX, i32::MAX, i32::MIN, i32::MIN);
+        assert_eq!(rect, Rect::new(i32::MIN, i32::MIN, u32::MAX, u32::MAX));
+        assert_eq!((rect.right(), rect.bottom()), (i32::MAX, i32::MAX));
+        assert_eq!(
+            Rect::from_corners(i32::MAX, 0, i32::MAX, 0),
+            Rect::new(i32::MAX, 0, 1, 1)
+        );
+        let bounds = Rect::new(0, 0, 4, 4);
+        assert_eq!(rect.intersect(&bounds), Some(bounds));
+        assert_eq!(rect.union(&bounds), rect);
+    }
+}
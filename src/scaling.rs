@@ -0,0 +1,216 @@
+use winit::dpi::{LogicalSize, PhysicalSize};
+
+/// How the pixel buffer is mapped onto a surface of a different size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScalingMode {
+    /// Cover the whole surface, ignoring the aspect ratio.
+    #[default]
+    Stretch,
+    /// Largest size that keeps the aspect ratio, the rest is filled with bars.
+    Fit,
+    /// Largest whole multiple of the buffer size that fits, centered.
+    /// Falls back to `Fit` when the surface is smaller than the buffer.
+    PixelPerfect,
+    /// Smallest size that keeps the aspect ratio and covers the whole
+    /// surface, cropping the buffer edges that don't fit.
+    Fill,
+}
+
+/// Area of the surface the pixel buffer is drawn to, in surface pixels.
+/// With `ScalingMode::Fill` it extends past the surface edges.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ScalingMode {
+    pub fn viewport(self, buffer: LogicalSize<u32>, surface: PhysicalSize<u32>) -> Viewport {
+        let (bw, bh) = (buffer.width as f32, buffer.height as f32);
+        let (sw, sh) = (surface.width as f32, surface.height as f32);
+
+        let scale = match self {
+            ScalingMode::Stretch => {
+                return Viewport {
+                    x: 0.0,
+                    y: 0.0,
+                    width: sw,
+                    height: sh,
+                }
+            }
+            ScalingMode::Fit => (sw / bw).min(sh / bh),
+            ScalingMode::PixelPerfect => {
+                let fit = (sw / bw).min(sh / bh);
+                if fit >= 1.0 {
+                    fit.floor()
+                } else {
+                    fit
+                }
+            }
+            ScalingMode::Fill => (sw / bw).max(sh / bh),
+        };
+
+        let width = bw * scale;
+        let height = bh * scale;
+        let (mut x, mut y) = ((sw - width) / 2.0, (sh - height) / 2.0);
+        if self == ScalingMode::PixelPerfect {
+            // keep buffer pixels on whole surface pixels
+            x = x.floor();
+            y = y.floor();
+        }
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+impl Viewport {
+    /// Buffer pixel under a surface position, `None` outside the buffer.
+    pub fn surface_to_buffer(
+        &self,
+        buffer: LogicalSize<u32>,
+        x: f64,
+        y: f64,
+    ) -> Option<(u32, u32)> {
+        let bx = ((x - self.x as f64) / self.width as f64 * buffer.width as f64).floor();
+        let by = ((y - self.y as f64) / self.height as f64 * buffer.height as f64).floor();
+        let inside =
+            (0.0..buffer.width as f64).contains(&bx) && (0.0..buffer.height as f64).contains(&by);
+        inside.then_some((bx as u32, by as u32))
+    }
+
+    /// Surface position of the center of a buffer pixel.
+    pub fn buffer_to_surface(&self, buffer: LogicalSize<u32>, x: u32, y: u32) -> (f64, f64) {
+        let sx = self.x as f64 + (x as f64 + 0.5) * self.width as f64 / buffer.width as f64;
+        let sy = self.y as f64 + (y as f64 + 0.5) * self.height as f64 / buffer.height as f64;
+        (sx, sy)
+    }
+
+    // scale and offset applied in clip space to the [-1, 1] quad by the vertex shader
+    pub(crate) fn clip_transform(&self, surface: PhysicalSize<u32>) -> [f32; 4] {
+        let (sw, sh) = (surface.width as f32, surface.height as f32);
+        let center_x = (self.x + self.width / 2.0) / sw * 2.0 - 1.0;
+        // clip space y points up, surface y points down
+        let center_y = 1.0 - (self.y + self.height / 2.0) / sh * 2.0;
+        [self.width / sw, self.height / sh, center_x, center_y]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUFFER: LogicalSize<u32> = LogicalSize::new(320, 200);
+
+    fn viewport(mode: ScalingMode, width: u32, height: u32) -> Viewport {
+        mode.viewport(BUFFER, PhysicalSize::new(width, height))
+    }
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> Viewport {
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn viewports_of_every_mode() {
+        assert_eq!(
+            viewport(ScalingMode::Stretch, 1000, 500),
+            rect(0.0, 0.0, 1000.0, 500.0)
+        );
+        assert_eq!(
+            viewport(ScalingMode::Fit, 1000, 500),
+            rect(100.0, 0.0, 800.0, 500.0)
+        );
+        assert_eq!(
+            viewport(ScalingMode::Fill, 1000, 500),
+            rect(0.0, -62.5, 1000.0, 625.0)
+        );
+        assert_eq!(
+            viewport(ScalingMode::PixelPerfect, 1000, 500),
+            rect(180.0, 50.0, 640.0, 400.0)
+        );
+        // odd leftovers still put buffer pixels on whole surface pixels
+        assert_eq!(
+            viewport(ScalingMode::PixelPerfect, 641, 401),
+            rect(0.0, 0.0, 640.0, 400.0)
+        );
+    }
+
+    #[test]
+    fn pixel_perfect_shrinks_like_fit_below_the_buffer_size() {
+        for (width, height) in [(160, 100), (200, 200)] {
+            let pixel_perfect = viewport(ScalingMode::PixelPerfect, width, height);
+            let fit = viewport(ScalingMode::Fit, width, height);
+            assert_eq!(
+                (pixel_perfect.width, pixel_perfect.height),
+                (fit.width, fit.height)
+            );
+            assert_eq!(
+                (pixel_perfect.x, pixel_perfect.y),
+                (fit.x.floor(), fit.y.floor())
+            );
+        }
+        assert_eq!(
+            viewport(ScalingMode::Fit, 160, 160),
+            rect(0.0, 30.0, 160.0, 100.0)
+        );
+    }
+
+    #[test]
+    fn clip_transform_places_the_viewport() {
+        let surface = PhysicalSize::new(1000, 500);
+        let full = viewport(ScalingMode::Stretch, 1000, 500);
+        assert_eq!(full.clip_transform(surface), [1.0, 1.0, 0.0, 0.0]);
+        let corner = rect(0.0, 0.0, 500.0, 250.0);
+        assert_eq!(corner.clip_transform(surface), [0.5, 0.5, -0.5, 0.5]);
+    }
+
+    #[test]
+    fn surface_positions_map_to_buffer_pixels() {
+        // 320x200 letterboxed into 1000x500, 2.5 surface pixels per buffer pixel
+        let fit = viewport(ScalingMode::Fit, 1000, 500);
+        assert_eq!(fit.surface_to_buffer(BUFFER, 100.0, 0.0), Some((0, 0)));
+        assert_eq!(fit.surface_to_buffer(BUFFER, 102.4, 2.4), Some((0, 0)));
+        assert_eq!(fit.surface_to_buffer(BUFFER, 102.5, 2.5), Some((1, 1)));
+        assert_eq!(
+            fit.surface_to_buffer(BUFFER, 899.9, 499.9),
+            Some((319, 199))
+        );
+        // the bars are outside the buffer
+        assert_eq!(fit.surface_to_buffer(BUFFER, 99.9, 10.0), None);
+        assert_eq!(fit.surface_to_buffer(BUFFER, 900.0, 10.0), None);
+        assert_eq!(fit.surface_to_buffer(BUFFER, 500.0, -0.1), None);
+
+        // cropped edges are outside the surface but still in the buffer
+        let fill = viewport(ScalingMode::Fill, 1000, 500);
+        assert_eq!(fill.surface_to_buffer(BUFFER, 0.0, 0.0), Some((0, 20)));
+    }
+
+    #[test]
+    fn buffer_pixels_map_back_to_their_centers() {
+        let fit = viewport(ScalingMode::Fit, 1000, 500);
+        assert_eq!(fit.buffer_to_surface(BUFFER, 0, 0), (101.25, 1.25));
+        assert_eq!(fit.buffer_to_surface(BUFFER, 319, 199), (898.75, 498.75));
+        for mode in [
+            ScalingMode::Stretch,
+            ScalingMode::Fit,
+            ScalingMode::PixelPerfect,
+            ScalingMode::Fill,
+        ] {
+            let viewport = viewport(mode, 1280, 720);
+            for (x, y) in [(0, 0), (17, 99), (319, 199)] {
+                let (sx, sy) = viewport.buffer_to_surface(BUFFER, x, y);
+                assert_eq!(viewport.surface_to_buffer(BUFFER, sx, sy), Some((x, y)));
+            }
+        }
+    }
+}
@@ -7,15 +7,26 @@ struct VertexOutput {
     @location(0) tex_coords: vec2<f32>,
 };
 
+// places the quad on the surface, see ScalingMode
+struct Transform {
+    scale: vec2<f32>,
+    offset: vec2<f32>,
+};
+
 @group(0) @binding(0)
 var t_diffuse: texture_2d<f32>;
 @group(0) @binding(1)
 var s_diffuse: sampler;
+@group(0) @binding(2)
+var<uniform> transform: Transform;
+// 256x1 colors of an indexed pixel buffer, see Palette
+@group(0) @binding(3)
+var t_palette: texture_2d<f32>;
 
 @vertex
 fn vs_main(in: VertexInput) -> VertexOutput {
     var out: VertexOutput;
-    out.clip_position = vec4<f32>(in.position, 0.0, 1.0);
+    out.clip_position = vec4<f32>(in.position * transform.scale + transform.offset, 0.0, 1.0);
     out.tex_coords = in.position * 0.5 + 0.5;
     out.tex_coords.y = 1.0 - out.tex_coords.y; // Flip Y coordinate
     return out;
@@ -25,3 +36,34 @@ fn vs_main(in: VertexInput) -> VertexOutput {
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     return textureSample(t_diffuse, s_diffuse, in.tex_coords);
 }
+
+// sRGB transfer function for swapchains that don't encode on write
+fn linear_to_srgb(linear: vec3<f32>) -> vec3<f32> {
+    let low = linear * 12.92;
+    let high = 1.055 * pow(linear, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, linear <= vec3<f32>(0.0031308));
+}
+
+@fragment
+fn fs_main_encode_srgb(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    return vec4<f32>(linear_to_srgb(color.rgb), color.a);
+}
+
+// the indexed texture stores index / 255 in its red channel
+fn palette_color(tex_coords: vec2<f32>) -> vec4<f32> {
+    let value = textureSample(t_diffuse, s_diffuse, tex_coords).r;
+    let index = u32(round(value * 255.0));
+    return textureLoad(t_palette, vec2<u32>(index, 0u), 0);
+}
+
+@fragment
+fn fs_main_indexed(in: VertexOutput) -> @location(0) vec4<f32> {
+    return palette_color(in.tex_coords);
+}
+
+@fragment
+fn fs_main_indexed_encode_srgb(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = palette_color(in.tex_coords);
+    return vec4<f32>(linear_to_srgb(color.rgb), color.a);
+}
@@ -0,0 +1,367 @@
+use crate::{canvas::Canvas, color::Color, rect::Rect};
+
+/// An owned image of tightly packed RGBA rows with straight alpha, the
+/// source for blits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Fully transparent image.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; rgba_len(width, height)],
+        }
+    }
+
+    /// Takes `width * height` RGBA pixels, such as decoded from a PNG.
+    pub fn from_rgba(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            rgba_len(width, height),
+            "pixel data doesn't match {width}x{height}"
+        );
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn bounds(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn get_pixel(&self, x: i32, y: i32) -> Option<Color> {
+        if !self.bounds().contains(x, y) {
+            return None;
+        }
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        let p = &self.pixels[index..index + 4];
+        Some(Color::rgba(p[0], p[1], p[2], p[3]))
+    }
+
+    /// Canvas drawing into the image, for building sprites at runtime.
+    pub fn canvas(&mut self) -> Canvas<'_> {
+        Canvas::new(&mut self.pixels, self.width, self.height)
+    }
+}
+
+// bytes of `width * height` RGBA pixels
+fn rgba_len(width: u32, height: u32) -> usize {
+    (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|len| len.checked_mul(4))
+        .unwrap_or_else(|| panic!("{width}x{height} RGBA pixels overflow the address space"))
+}
+
+/// How [`Canvas::blit_with`] copies an image.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Blit {
+    /// Part of the image to copy, all of it when `None`.
+    pub source: Option<Rect>,
+    /// Mirror left to right.
+    pub flip_x: bool,
+    /// Mirror top to bottom.
+    pub flip_y: bool,
+    /// Pixels with this RGB value are skipped, whatever their alpha.
+    pub color_key: Option<Color>,
+    /// Size on the canvas, overrides `scale`.
+    pub size: Option<(u32, u32)>,
+    /// Factors the source size is multiplied by on the canvas.
+    pub scale: Option<(f32, f32)>,
+}
+
+impl Blit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_source(mut self, source: Rect) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn with_flip(mut self, flip_x: bool, flip_y: bool) -> Self {
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+        self
+    }
+
+    pub fn with_color_key(mut self, color: Color) -> Self {
+        self.color_key = Some(color);
+        self
+    }
+
+    /// Stretches the copy to `width` by `height` pixels.
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+
+    /// Scales the copy by `x` and `y`, rounded to whole pixels.
+    pub fn with_scale(mut self, x: f32, y: f32) -> Self {
+        self.scale = Some((x, y));
+        self
+    }
+}
+
+impl Canvas<'_> {
+    /// Draws the whole image with its top left corner at `x, y`, blending
+    /// by its alpha.
+    pub fn blit(&mut self, image: &Image, x: i32, y: i32) {
+        self.blit_with(image, x, y, &Blit::new());
+    }
+
+    /// Draws an image with the given options. Scaling picks the nearest
+    /// source pixel, so integer factors keep pixel art crisp. Rotated or
+    /// sheared transforms sample the image for every covered canvas pixel.
+    pub fn blit_with(&mut self, image: &Image, x: i32, y: i32, options: &Blit) {
+        let source = match options.source {
+            Some(source) => source.intersect(&image.bounds()),
+            None => Some(image.bounds()).filter(|bounds| !bounds.is_empty()),
+        };
+        let Some(source) = source else {
+            return;
+        };
+        let (width, height) = match (options.size, options.scale) {
+            (Some(size), _) => size,
+            (None, Some((sx, sy))) => (
+                (source.width as f32 * sx).round().max(0.0) as u32,
+                (source.height as f32 * sy).round().max(0.0) as u32,
+            ),
+            (None, None) => (source.width, source.height),
+        };
+        let destination = Rect::new(x, y, width, height);
+        if destination.is_empty() {
+            return;
+        }
+        let transform = self.transform();
+        match transform.pixel_offset() {
+            Some((dx, dy)) => {
+                // beyond the i32 range is outside any canvas
+                let (Some(x), Some(y)) = (x.checked_add(dx), y.checked_add(dy)) else {
+                    return;
+                };
+                let destination = Rect::new(x, y, width, height);
+                self.device_blit(image, source, destination, options);
+            }
+            None => self.transformed_blit(image, source, destination, options),
+        }
+    }
+
+    fn device_blit(&mut self, image: &Image, source: Rect, destination: Rect, options: &Blit) {
+        let Some(target) = self.clip().intersect(&destination) else {
+            return;
+        };
+        // nearest source pixel for a destination offset, exact for integer
+        // scale factors
+        let sample = |offset: i64, destination: u32, source: u32, flip: bool| -> i32 {
+            let s = ((2 * offset + 1) * source as i64 / (2 * destination as i64)) as i32;
+            if flip {
+                source as i32 - 1 - s
+            } else {
+                s
+            }
+        };
+        for dy in target.y..target.bottom() {
+            let offset_y = dy as i64 - destination.y as i64;
+            let sy = source.y + sample(offset_y, destination.height, source.height, options.flip_y);
+            for dx in target.x..target.right() {
+                let offset_x = dx as i64 - destination.x as i64;
+                let sx =
+                    source.x + sample(offset_x, destination.width, source.width, options.flip_x);
+                self.blit_pixel(image, sx, sy, dx, dy, options.color_key);
+            }
+        }
+        self.touch(target);
+    }
+
+    // maps every canvas pixel whose center lands inside the destination back
+    // into the source
+    fn transformed_blit(&mut self, image: &Image, source: Rect, destination: Rect, options: &Blit) {
+        let transform = self.transform();
+        let Some(inverse) = transform.inverse() else {
+            return;
+        };
+        let bounds = transform.bounding_rect(destination);
+        let Some(target) = self.clip().intersect(&bounds) else {
+            return;
+        };
+        let sample = |local: f32, start: i32, destination: u32, source: u32, flip: bool| {
+            let s = ((local - start as f32) * source as f32 / destination as f32).floor() as i32;
+            let s = s.clamp(0, source as i32 - 1);
+            if flip {
+                source as i32 - 1 - s
+            } else {
+                s
+            }
+        };
+        let (left, top) = (destination.x as f32, destination.y as f32);
+        let (right, bottom) = (destination.right() as f32, destination.bottom() as f32);
+        for dy in target.y..target.bottom() {
+            for dx in target.x..target.right() {
+                let (lx, ly) = inverse.apply(dx as f32 + 0.5, dy as f32 + 0.5);
+                if lx < left || lx >= right || ly < top || ly >= bottom {
+                    continue;
+                }
+                let sx = source.x
+                    + sample(
+                        lx,
+                        destination.x,
+                        destination.width,
+                        source.width,
+                        options.flip_x,
+                    );
+                let sy = source.y
+                    + sample(
+                        ly,
+                        destination.y,
+                        destination.height,
+                        source.height,
+                        options.flip_y,
+                    );
+                self.blit_pixel(image, sx, sy, dx, dy, options.color_key);
+            }
+        }
+        self.touch(target);
+    }
+
+    // copies one source pixel unless it matches the color key
+    fn blit_pixel(&mut self, image: &Image, sx: i32, sy: i32, x: i32, y: i32, key: Option<Color>) {
+        let Some(color) = image.get_pixel(sx, sy) else {
+            return;
+        };
+        if key.is_some_and(|k| (k.r, k.g, k.b) == (color.r, color.g, color.b)) {
+            return;
+        }
+        if let Some(index) = self.index(x, y) {
+            self.write(index, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{canvas::tests::buffer, format::PixelFormat, transform::Transform};
+
+    const A: Color = Color::rgb(255, 0, 0);
+    const B: Color = Color::rgb(0, 255, 0);
+    const C: Color = Color::rgb(0, 0, 255);
+    const D: Color = Color::rgb(255, 255, 0);
+    const CLEAR: Color = Color::TRANSPARENT;
+
+    // A B
+    // C D
+    fn image() -> Image {
+        let pixels = [A, B, C, D]
+            .iter()
+            .flat_map(|c| [c.r, c.g, c.b, c.a])
+            .collect();
+        Image::from_rgba(2, 2, pixels)
+    }
+
+    // every pixel of a canvas of `width` by `height` after `draw`, row by row
+    fn drawn(width: u32, height: u32, draw: impl FnOnce(&mut Canvas)) -> Vec<Color> {
+        let mut pixels = buffer(width, height, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, width, height);
+        draw(&mut canvas);
+        (0..height as i32)
+            .flat_map(|y| (0..width as i32).map(move |x| (x, y)))
+            .map(|(x, y)| canvas.get_pixel(x, y).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn blits_are_placed_and_clipped() {
+        let image = image();
+        let pixels = drawn(3, 3, |canvas| canvas.blit(&image, 1, 1));
+        assert_eq!(pixels, [CLEAR, CLEAR, CLEAR, CLEAR, A, B, CLEAR, C, D]);
+        let pixels = drawn(2, 2, |canvas| canvas.blit(&image, -1, 1));
+        assert_eq!(pixels, [CLEAR, CLEAR, B, CLEAR]);
+    }
+
+    #[test]
+    fn flips_mirror_the_image() {
+        let image = image();
+        let flip = |x, y| {
+            drawn(2, 2, |canvas| {
+                canvas.blit_with(&image, 0, 0, &Blit::new().with_flip(x, y))
+            })
+        };
+        assert_eq!(flip(true, false), [B, A, D, C]);
+        assert_eq!(flip(false, true), [C, D, A, B]);
+        assert_eq!(flip(true, true), [D, C, B, A]);
+    }
+
+    #[test]
+    fn color_key_skips_matching_pixels() {
+        let image = image();
+        let options = Blit::new().with_color_key(Color::rgba(0, 255, 0, 7));
+        let pixels = drawn(2, 2, |canvas| {
+            canvas.clear(Color::BLACK);
+            canvas.blit_with(&image, 0, 0, &options);
+        });
+        assert_eq!(pixels, [A, Color::BLACK, C, D]);
+    }
+
+    #[test]
+    fn scaling_picks_the_nearest_source_pixel() {
+        let image = image();
+        let pixels = drawn(4, 2, |canvas| {
+            canvas.blit_with(&image, 0, 0, &Blit::new().with_scale(2.0, 1.0));
+        });
+        assert_eq!(pixels, [A, A, B, B, C, C, D, D]);
+        let pixels = drawn(1, 1, |canvas| {
+            canvas.blit_with(&image, 0, 0, &Blit::new().with_size(1, 1));
+        });
+        assert_eq!(pixels, [D]);
+        let source = Rect::new(1, 0, 1, 2);
+        let pixels = drawn(2, 2, |canvas| {
+            let options = Blit::new().with_source(source).with_size(2, 2);
+            canvas.blit_with(&image, 0, 0, &options);
+        });
+        assert_eq!(pixels, [B, B, D, D]);
+    }
+
+    #[test]
+    fn transparent_pixels_are_blended() {
+        let image = Image::from_rgba(1, 1, vec![255, 255, 255, 0]);
+        let pixels = drawn(1, 1, |canvas| {
+            canvas.clear(Color::BLACK);
+            canvas.blit(&image, 0, 0);
+        });
+        assert_eq!(pixels, [Color::BLACK]);
+    }
+
+    #[test]
+    fn rotated_blits_sample_every_covered_pixel() {
+        let image = image();
+        // a quarter turn clockwise around the center of the canvas
+        let transform = Transform::translate(-1.0, -1.0)
+            .then(&Transform::rotate(std::f32::consts::FRAC_PI_2))
+            .then(&Transform::translate(1.0, 1.0));
+        let pixels = drawn(2, 2, |canvas| {
+            canvas.with_transform(transform, |canvas| canvas.blit(&image, 0, 0));
+        });
+        assert_eq!(pixels, [C, A, D, B]);
+    }
+}
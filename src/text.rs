@@ -0,0 +1,503 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use crate::{canvas::Canvas, color::Color, error::Error, rect::Rect};
+
+// one character of a bitmap font
+struct Glyph {
+    width: u32,
+    // top left corner relative to the pen, y measured from the top of the line
+    x: i32,
+    y: i32,
+    advance: u32,
+    // row major, true where the glyph is drawn
+    bits: Vec<bool>,
+}
+
+/// A bitmap font, either the built-in 8x8 one or loaded from a BDF file or
+/// an image with the glyphs laid out in a grid.
+pub struct Font {
+    line_height: u32,
+    glyphs: HashMap<char, Glyph>,
+}
+
+impl Font {
+    /// Monospace 8x8 font covering printable ASCII, from the public domain
+    /// font8x8 set.
+    pub fn builtin() -> &'static Font {
+        static BUILTIN: OnceLock<Font> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            let glyphs = (' '..='~')
+                .zip(FONT8X8_BASIC.iter())
+                .map(|(c, rows)| {
+                    let bits = rows
+                        .iter()
+                        .flat_map(|row| (0..8).map(move |x| row & (1 << x) != 0))
+                        .collect();
+                    (c, monospace_glyph(8, bits))
+                })
+                .collect();
+            Font {
+                line_height: 8,
+                glyphs,
+            }
+        })
+    }
+
+    /// Parses a font in the Glyph Bitmap Distribution Format.
+    pub fn from_bdf(source: &str) -> Result<Font, Error> {
+        let mut bounding_box = None;
+        let mut ascent = None;
+        let mut descent = None;
+        let mut glyphs = HashMap::new();
+
+        let mut lines = source.lines().enumerate();
+        while let Some((number, line)) = lines.next() {
+            let (keyword, rest) = split_keyword(line);
+            match keyword {
+                "FONTBOUNDINGBOX" => bounding_box = Some(bdf_numbers::<4>(number, rest)?),
+                "FONT_ASCENT" => ascent = Some(bdf_numbers::<1>(number, rest)?[0]),
+                "FONT_DESCENT" => descent = Some(bdf_numbers::<1>(number, rest)?[0]),
+                "STARTCHAR" => {
+                    let [width, height, x, y] = bounding_box
+                        .ok_or_else(|| bdf_error(number, "STARTCHAR before FONTBOUNDINGBOX"))?;
+                    let ascent = match ascent {
+                        Some(ascent) => ascent,
+                        None => checked_metric(number, height.checked_add(y))?,
+                    };
+                    let mut encoding = None;
+                    let mut advance = width;
+                    let mut bbx = [width, height, x, y];
+                    let mut bits = Vec::new();
+                    loop {
+                        let Some((number, line)) = lines.next() else {
+                            return Err(bdf_error(number, "missing ENDCHAR"));
+                        };
+                        let (keyword, rest) = split_keyword(line);
+                        match keyword {
+                            "ENCODING" => encoding = Some(bdf_numbers::<1>(number, rest)?[0]),
+                            "DWIDTH" => advance = bdf_numbers::<1>(number, rest)?[0],
+                            "BBX" => bbx = bdf_numbers::<4>(number, rest)?,
+                            "BITMAP" => {
+                                bits = read_bdf_bitmap(&mut lines, bbx[0], bbx[1])?;
+                                continue;
+                            }
+                            "ENDCHAR" => break,
+                            _ => {}
+                        }
+                    }
+                    // negative encodings are unmapped glyphs
+                    let Some(c) = encoding
+                        .and_then(|e| u32::try_from(e).ok())
+                        .and_then(char::from_u32)
+                    else {
+                        continue;
+                    };
+                    let [width, height, x, y] = bbx;
+                    let top = y
+                        .checked_add(height)
+                        .and_then(|top| ascent.checked_sub(top));
+                    glyphs.insert(
+                        c,
+                        Glyph {
+                            width: width.max(0) as u32,
+                            x,
+                            y: checked_metric(number, top)?,
+                            advance: advance.max(0) as u32,
+                            bits,
+                        },
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        let [_, height, _, y] =
+            bounding_box.ok_or_else(|| Error::InvalidFont("missing FONTBOUNDINGBOX".into()))?;
+        let ascent = ascent.or_else(|| height.checked_add(y));
+        let descent = descent.or_else(|| y.checked_neg());
+        let line_height = ascent
+            .zip(descent)
+            .and_then(|(ascent, descent)| ascent.checked_add(descent))
+            .ok_or_else(|| Error::InvalidFont("font metrics out of range".into()))?;
+        Ok(Font {
+            line_height: line_height.max(0) as u32,
+            glyphs,
+        })
+    }
+
+    /// Cuts a monospace font out of an RGBA image holding `cell_width` by
+    /// `cell_height` glyphs in rows, starting with `first` and counting up
+    /// left to right. Pixels that are both opaque and bright are drawn, so
+    /// light glyphs on a black or transparent background both work.
+    pub fn from_image_grid(
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        cell_width: u32,
+        cell_height: u32,
+        first: char,
+    ) -> Result<Font, Error> {
+        let size = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|size| size.checked_mul(4));
+        if size != Some(pixels.len()) {
+            return Err(Error::InvalidFont(format!(
+                "image data doesn't match {width}x{height}"
+            )));
+        }
+        if cell_width == 0 || cell_height == 0 || cell_width > width || cell_height > height {
+            return Err(Error::InvalidFont(format!(
+                "{cell_width}x{cell_height} cells don't fit a {width}x{height} image"
+            )));
+        }
+        let (columns, rows) = (width / cell_width, height / cell_height);
+        let glyphs = (0..rows * columns)
+            .filter_map(|cell| Some((char::from_u32((first as u32).checked_add(cell)?)?, cell)))
+            .map(|(c, cell)| {
+                let left = cell % columns * cell_width;
+                let top = cell / columns * cell_height;
+                let bits = (top..top + cell_height)
+                    .flat_map(|y| (left..left + cell_width).map(move |x| (x, y)))
+                    .map(|(x, y)| {
+                        let index = (y as usize * width as usize + x as usize) * 4;
+                        let p = &pixels[index..][..4];
+                        let brightness = (p[0] as u32 + p[1] as u32 + p[2] as u32) / 3;
+                        p[3] >= 128 && brightness >= 128
+                    })
+                    .collect();
+                (c, monospace_glyph(cell_width, bits))
+            })
+            .collect();
+        Ok(Font {
+            line_height: cell_height,
+            glyphs,
+        })
+    }
+
+    /// Distance between the tops of two lines of text, unscaled.
+    pub fn line_height(&self) -> u32 {
+        self.line_height
+    }
+
+    /// Width and height `text` takes up when drawn at `scale`.
+    pub fn measure(&self, text: &str, scale: u32) -> (u32, u32) {
+        let scale = scale.max(1);
+        let width = text
+            .split('\n')
+            .map(|line| {
+                line.chars()
+                    .filter_map(|c| self.glyph(c))
+                    .fold(0u32, |width, g| width.saturating_add(g.advance))
+            })
+            .max()
+            .unwrap_or(0);
+        let lines = text.split('\n').count() as u32;
+        let height = lines.saturating_mul(self.line_height);
+        (width.saturating_mul(scale), height.saturating_mul(scale))
+    }
+
+    // missing characters show up as a question mark if the font has one
+    fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(&c).or_else(|| self.glyphs.get(&'?'))
+    }
+}
+
+impl Canvas<'_> {
+    /// Draws `text` with the built-in font, its top left corner at `x, y`.
+    /// Every font pixel becomes a `scale` by `scale` block, `\n` starts a new
+    /// line below `x`.
+    pub fn draw_text(&mut self, x: i32, y: i32, text: &str, color: Color, scale: u32) {
+        self.draw_text_with(Font::builtin(), x, y, text, color, scale);
+    }
+
+    /// Like [`Canvas::draw_text`] with a custom font.
+    pub fn draw_text_with(
+        &mut self,
+        font: &Font,
+        x: i32,
+        y: i32,
+        text: &str,
+        color: Color,
+        scale: u32,
+    ) {
+        // a step has to stay a positive i32
+        let scale = scale.clamp(1, i32::MAX as u32);
+        let step = scale as i32;
+        let (mut pen_x, mut pen_y) = (x, y);
+        for c in text.chars() {
+            if c == '\n' {
+                pen_x = x;
+                pen_y = pen_y.saturating_add(scaled(font.line_height, step));
+                continue;
+            }
+            let Some(glyph) = font.glyph(c) else {
+                continue;
+            };
+            // metrics come from font files, so they may be far out of range
+            let left = pen_x.saturating_add(glyph.x.saturating_mul(step));
+            let top = pen_y.saturating_add(glyph.y.saturating_mul(step));
+            for (row, bits) in glyph.bits.chunks(glyph.width.max(1) as usize).enumerate() {
+                // one rectangle per run of set bits
+                let mut column = 0;
+                while column < bits.len() {
+                    if !bits[column] {
+                        column += 1;
+                        continue;
+                    }
+                    let start = column;
+                    while column < bits.len() && bits[column] {
+                        column += 1;
+                    }
+                    self.fill_rect(
+                        Rect::new(
+                            left.saturating_add(scaled(start as u32, step)),
+                            top.saturating_add(scaled(row as u32, step)),
+                            ((column - start) as u32).saturating_mul(scale),
+                            scale,
+                        ),
+                        color,
+                    );
+                }
+            }
+            pen_x = pen_x.saturating_add(scaled(glyph.advance, step));
+        }
+    }
+}
+
+// font units times the scale in pixels, saturating
+fn scaled(units: u32, step: i32) -> i32 {
+    (units as i64 * step as i64).clamp(i32::MIN as i64, i32::MAX as i64) as i32
+}
+
+fn monospace_glyph(width: u32, bits: Vec<bool>) -> Glyph {
+    Glyph {
+        width,
+        x: 0,
+        y: 0,
+        advance: width,
+        bits,
+    }
+}
+
+fn split_keyword(line: &str) -> (&str, &str) {
+    let line = line.trim();
+    line.split_once(char::is_whitespace)
+        .map_or((line, ""), |(keyword, rest)| (keyword, rest.trim()))
+}
+
+fn bdf_error(line: usize, reason: &str) -> Error {
+    Error::InvalidFont(format!("line {}: {reason}", line + 1))
+}
+
+// a metric worked out from header values, none if it overflowed
+fn checked_metric(line: usize, metric: Option<i32>) -> Result<i32, Error> {
+    metric.ok_or_else(|| bdf_error(line, "font metrics out of range"))
+}
+
+// the first N integers after a keyword
+fn bdf_numbers<const N: usize>(line: usize, rest: &str) -> Result<[i32; N], Error> {
+    let mut numbers = [0; N];
+    let mut words = rest.split_whitespace();
+    for number in &mut numbers {
+        *number = words
+            .next()
+            .and_then(|word| word.parse().ok())
+            .ok_or_else(|| bdf_error(line, &format!("expected {N} numbers")))?;
+    }
+    Ok(numbers)
+}
+
+// hex rows of a BITMAP section, most significant bit first
+fn read_bdf_bitmap<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    width: i32,
+    height: i32,
+) -> Result<Vec<bool>, Error> {
+    let (width, height) = (width.max(0) as usize, height.max(0) as usize);
+    // the size comes from the file, rows are checked before they are added
+    let mut bits = Vec::new();
+    for _ in 0..height {
+        let Some((number, line)) = lines.next() else {
+            return Err(Error::InvalidFont("bitmap ends early".into()));
+        };
+        let line = line.trim();
+        // hex digits are ASCII, other characters would split a byte pair
+        if !line.is_ascii() {
+            return Err(bdf_error(number, "invalid bitmap row"));
+        }
+        let row: Vec<u8> = (0..line.len() / 2)
+            .map(|i| u8::from_str_radix(&line[i * 2..i * 2 + 2], 16))
+            .collect::<Result<_, _>>()
+            .map_err(|_| bdf_error(number, "invalid bitmap row"))?;
+        if row.len() * 8 < width {
+            return Err(bdf_error(number, "bitmap row is too short"));
+        }
+        bits.extend((0..width).map(|x| row[x / 8] & (0x80 >> (x % 8)) != 0));
+    }
+    Ok(bits)
+}
+
+// printable ASCII from font8x8 by Daniel Hepper, public domain, one byte per
+// row with the least significant bit on the left
+const FONT8X8_BASIC: [[u8; 8]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // space
+    [0x18, 0x3C, 0x3C, 0x18, 0x18, 0x00, 0x18, 0x00], // !
+    [0x36, 0x36, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // "
+    [0x36, 0x36, 0x7F, 0x36, 0x7F, 0x36, 0x36, 0x00], // #
+    [0x0C, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x0C, 0x00], // $
+    [0x00, 0x63, 0x33, 0x18, 0x0C, 0x66, 0x63, 0x00], // %
+    [0x1C, 0x36, 0x1C, 0x6E, 0x3B, 0x33, 0x6E, 0x00], // &
+    [0x06, 0x06, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00], // '
+    [0x18, 0x0C, 0x06, 0x06, 0x06, 0x0C, 0x18, 0x00], // (
+    [0x06, 0x0C, 0x18, 0x18, 0x18, 0x0C, 0x06, 0x00], // )
+    [0x00, 0x66, 0x3C, 0xFF, 0x3C, 0x66, 0x00, 0x00], // *
+    [0x00, 0x0C, 0x0C, 0x3F, 0x0C, 0x0C, 0x00, 0x00], // +
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ,
+    [0x00, 0x00, 0x00, 0x3F, 0x00, 0x00, 0x00, 0x00], // -
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00], // .
+    [0x60, 0x30, 0x18, 0x0C, 0x06, 0x03, 0x01, 0x00], // /
+    [0x3E, 0x63, 0x73, 0x7B, 0x6F, 0x67, 0x3E, 0x00], // 0
+    [0x0C, 0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x3F, 0x00], // 1
+    [0x1E, 0x33, 0x30, 0x1C, 0x06, 0x33, 0x3F, 0x00], // 2
+    [0x1E, 0x33, 0x30, 0x1C, 0x30, 0x33, 0x1E, 0x00], // 3
+    [0x38, 0x3C, 0x36, 0x33, 0x7F, 0x30, 0x78, 0x00], // 4
+    [0x3F, 0x03, 0x1F, 0x30, 0x30, 0x33, 0x1E, 0x00], // 5
+    [0x1C, 0x06, 0x03, 0x1F, 0x33, 0x33, 0x1E, 0x00], // 6
+    [0x3F, 0x33, 0x30, 0x18, 0x0C, 0x0C, 0x0C, 0x00], // 7
+    [0x1E, 0x33, 0x33, 0x1E, 0x33, 0x33, 0x1E, 0x00], // 8
+    [0x1E, 0x33, 0x33, 0x3E, 0x30, 0x18, 0x0E, 0x00], // 9
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x00], // :
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ;
+    [0x18, 0x0C, 0x06, 0x03, 0x06, 0x0C, 0x18, 0x00], // <
+    [0x00, 0x00, 0x3F, 0x00, 0x00, 0x3F, 0x00, 0x00], // =
+    [0x06, 0x0C, 0x18, 0x30, 0x18, 0x0C, 0x06, 0x00], // >
+    [0x1E, 0x33, 0x30, 0x18, 0x0C, 0x00, 0x0C, 0x00], // ?
+    [0x3E, 0x63, 0x7B, 0x7B, 0x7B, 0x03, 0x1E, 0x00], // @
+    [0x0C, 0x1E, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x00], // A
+    [0x3F, 0x66, 0x66, 0x3E, 0x66, 0x66, 0x3F, 0x00], // B
+    [0x3C, 0x66, 0x03, 0x03, 0x03, 0x66, 0x3C, 0x00], // C
+    [0x1F, 0x36, 0x66, 0x66, 0x66, 0x36, 0x1F, 0x00], // D
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x46, 0x7F, 0x00], // E
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x06, 0x0F, 0x00], // F
+    [0x3C, 0x66, 0x03, 0x03, 0x73, 0x66, 0x7C, 0x00], // G
+    [0x33, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x33, 0x00], // H
+    [0x1E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // I
+    [0x78, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E, 0x00], // J
+    [0x67, 0x66, 0x36, 0x1E, 0x36, 0x66, 0x67, 0x00], // K
+    [0x0F, 0x06, 0x06, 0x06, 0x46, 0x66, 0x7F, 0x00], // L
+    [0x63, 0x77, 0x7F, 0x7F, 0x6B, 0x63, 0x63, 0x00], // M
+    [0x63, 0x67, 0x6F, 0x7B, 0x73, 0x63, 0x63, 0x00], // N
+    [0x1C, 0x36, 0x63, 0x63, 0x63, 0x36, 0x1C, 0x00], // O
+    [0x3F, 0x66, 0x66, 0x3E, 0x06, 0x06, 0x0F, 0x00], // P
+    [0x1E, 0x33, 0x33, 0x33, 0x3B, 0x1E, 0x38, 0x00], // Q
+    [0x3F, 0x66, 0x66, 0x3E, 0x36, 0x66, 0x67, 0x00], // R
+    [0x1E, 0x33, 0x07, 0x0E, 0x38, 0x33, 0x1E, 0x00], // S
+    [0x3F, 0x2D, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // T
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3F, 0x00], // U
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // V
+    [0x63, 0x63, 0x63, 0x6B, 0x7F, 0x77, 0x63, 0x00], // W
+    [0x63, 0x63, 0x36, 0x1C, 0x1C, 0x36, 0x63, 0x00], // X
+    [0x33, 0x33, 0x33, 0x1E, 0x0C, 0x0C, 0x1E, 0x00], // Y
+    [0x7F, 0x63, 0x31, 0x18, 0x4C, 0x66, 0x7F, 0x00], // Z
+    [0x1E, 0x06, 0x06, 0x06, 0x06, 0x06, 0x1E, 0x00], // [
+    [0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x40, 0x00], // backslash
+    [0x1E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x1E, 0x00], // ]
+    [0x08, 0x1C, 0x36, 0x63, 0x00, 0x00, 0x00, 0x00], // ^
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF], // _
+    [0x0C, 0x0C, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00], // `
+    [0x00, 0x00, 0x1E, 0x30, 0x3E, 0x33, 0x6E, 0x00], // a
+    [0x07, 0x06, 0x06, 0x3E, 0x66, 0x66, 0x3B, 0x00], // b
+    [0x00, 0x00, 0x1E, 0x33, 0x03, 0x33, 0x1E, 0x00], // c
+    [0x38, 0x30, 0x30, 0x3E, 0x33, 0x33, 0x6E, 0x00], // d
+    [0x00, 0x00, 0x1E, 0x33, 0x3F, 0x03, 0x1E, 0x00], // e
+    [0x1C, 0x36, 0x06, 0x0F, 0x06, 0x06, 0x0F, 0x00], // f
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x1F], // g
+    [0x07, 0x06, 0x36, 0x6E, 0x66, 0x66, 0x67, 0x00], // h
+    [0x0C, 0x00, 0x0E, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // i
+    [0x30, 0x00, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E], // j
+    [0x07, 0x06, 0x66, 0x36, 0x1E, 0x36, 0x67, 0x00], // k
+    [0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // l
+    [0x00, 0x00, 0x33, 0x7F, 0x7F, 0x6B, 0x63, 0x00], // m
+    [0x00, 0x00, 0x1F, 0x33, 0x33, 0x33, 0x33, 0x00], // n
+    [0x00, 0x00, 0x1E, 0x33, 0x33, 0x33, 0x1E, 0x00], // o
+    [0x00, 0x00, 0x3B, 0x66, 0x66, 0x3E, 0x06, 0x0F], // p
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x78], // q
+    [0x00, 0x00, 0x3B, 0x6E, 0x66, 0x06, 0x0F, 0x00], // r
+    [0x00, 0x00, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x00], // s
+    [0x08, 0x0C, 0x3E, 0x0C, 0x0C, 0x2C, 0x18, 0x00], // t
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x6E, 0x00], // u
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // v
+    [0x00, 0x00, 0x63, 0x6B, 0x7F, 0x7F, 0x36, 0x00], // w
+    [0x00, 0x00, 0x63, 0x36, 0x1C, 0x36, 0x63, 0x00], // x
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x3E, 0x30, 0x1F], // y
+    [0x00, 0x00, 0x3F, 0x19, 0x0C, 0x26, 0x3F, 0x00], // z
+    [0x38, 0x0C, 0x0C, 0x07, 0x0C, 0x0C, 0x38, 0x00], // {
+    [0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x18, 0x00], // |
+    [0x07, 0x0C, 0x0C, 0x38, 0x0C, 0x0C, 0x07, 0x00], // }
+    [0x6E, 0x3B, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ~
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        canvas::tests::{buffer, painted},
+        format::PixelFormat,
+    };
+
+    const RED: Color = Color::rgb(255, 0, 0);
+
+    fn bdf(bounding_box: &str, bbx: &str, row: &str) -> Result<Font, Error> {
+        Font::from_bdf(&format!(
+            "STARTFONT 2.1\nFONTBOUNDINGBOX {bounding_box}\nSTARTCHAR a\nENCODING 97\n\
+             BBX {bbx}\nBITMAP\n{row}\nENDCHAR\nENDFONT\n"
+        ))
+    }
+
+    fn invalid(font: Result<Font, Error>) -> bool {
+        matches!(font, Err(Error::InvalidFont(_)))
+    }
+
+    #[test]
+    fn bdf_glyphs_are_read_most_significant_bit_first() {
+        let font = bdf("4 2 0 0", "4 2 1 0", "90\n60").unwrap();
+        assert_eq!(font.line_height(), 2);
+        assert_eq!(font.measure("aa", 2), (16, 4));
+        let mut pixels = buffer(6, 2, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 6, 2);
+        canvas.draw_text_with(&font, 0, 0, "a", RED, 1);
+        assert_eq!(painted(&canvas, RED), [(1, 0), (4, 0), (2, 1), (3, 1)]);
+    }
+
+    #[test]
+    fn malformed_fonts_are_rejected() {
+        assert!(invalid(bdf("8 1 0 0", "8 1 0 0", "aé")));
+        assert!(invalid(bdf("8 1 0 0", "8 1 0 0", "f")));
+        assert!(invalid(bdf("8 2147483647 0 2147483647", "8 1 0 0", "ff")));
+        assert!(invalid(bdf("8 1 0 -2147483648", "8 1 0 0", "ff")));
+        assert!(invalid(Font::from_image_grid(
+            &[],
+            u32::MAX,
+            u32::MAX,
+            1,
+            1,
+            'a'
+        )));
+    }
+
+    #[test]
+    fn extreme_metrics_and_scales_are_clipped() {
+        // glyphs far off the pen are still drawn, clipped away
+        let font = bdf("8 1 0 0", "8 1 2147483647 -2147483647", "ff").unwrap();
+        let mut pixels = buffer(4, 4, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 4, 4);
+        canvas.draw_text_with(&font, 0, 0, "aa\na", RED, 3);
+        assert_eq!(canvas.dirty_rect(), None);
+
+        // the top left pixel of the block is the only one on the canvas
+        canvas.draw_text(0, 0, "A\nA", RED, u32::MAX);
+        canvas.draw_text(-1, 0, "!", RED, u32::MAX);
+        assert_eq!(painted(&canvas, RED), []);
+        assert_eq!(Font::builtin().measure("A", u32::MAX), (u32::MAX, u32::MAX));
+    }
+}
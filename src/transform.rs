@@ -0,0 +1,234 @@
+use crate::rect::{span, Rect};
+
+/// A 2D affine transform, mapping `(x, y)` to
+/// `(xx * x + xy * y + x0, yx * x + yy * y + y0)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub xx: f32,
+    pub yx: f32,
+    pub xy: f32,
+    pub yy: f32,
+    pub x0: f32,
+    pub y0: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        xx: 1.0,
+        yx: 0.0,
+        xy: 0.0,
+        yy: 1.0,
+        x0: 0.0,
+        y0: 0.0,
+    };
+
+    pub fn translate(x: f32, y: f32) -> Self {
+        Self {
+            x0: x,
+            y0: y,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn scale(x: f32, y: f32) -> Self {
+        Self {
+            xx: x,
+            yy: y,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Rotation by `angle` radians around the origin, clockwise on screen
+    /// like the angles of [`Canvas::arc`](crate::canvas::Canvas::arc).
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self {
+            xx: cos,
+            yx: sin,
+            xy: -sin,
+            yy: cos,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// This transform followed by `next`.
+    pub fn then(&self, next: &Transform) -> Transform {
+        Transform {
+            xx: next.xx * self.xx + next.xy * self.yx,
+            yx: next.yx * self.xx + next.yy * self.yx,
+            xy: next.xx * self.xy + next.xy * self.yy,
+            yy: next.yx * self.xy + next.yy * self.yy,
+            x0: next.xx * self.x0 + next.xy * self.y0 + next.x0,
+            y0: next.yx * self.x0 + next.yy * self.y0 + next.y0,
+        }
+    }
+
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.xx * x + self.xy * y + self.x0,
+            self.yx * x + self.yy * y + self.y0,
+        )
+    }
+
+    /// The transform undoing this one, `None` if it collapses the plane.
+    pub fn inverse(&self) -> Option<Transform> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let (xx, yx, xy, yy) = (self.yy / det, -self.yx / det, -self.xy / det, self.xx / det);
+        Some(Transform {
+            xx,
+            yx,
+            xy,
+            yy,
+            x0: -(xx * self.x0 + xy * self.y0),
+            y0: -(yx * self.x0 + yy * self.y0),
+        })
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    // no rotation or shear, rectangles stay rectangles
+    pub(crate) fn is_axis_aligned(&self) -> bool {
+        self.xy == 0.0 && self.yx == 0.0
+    }
+
+    // the offset in whole pixels, if that is all the transform does
+    pub(crate) fn pixel_offset(&self) -> Option<(i32, i32)> {
+        let whole = |v: f32| v.fract() == 0.0 && v.abs() < i32::MAX as f32;
+        (self.xx == 1.0 && self.yy == 1.0 && self.is_axis_aligned())
+            .then_some((self.x0, self.y0))
+            .filter(|&(x, y)| whole(x) && whole(y))
+            .map(|(x, y)| (x as i32, y as i32))
+    }
+
+    // how much lengths grow, averaged over all directions
+    pub(crate) fn length_scale(&self) -> f32 {
+        self.determinant().abs().sqrt()
+    }
+
+    // canvas pixel the center of a local pixel lands in
+    pub(crate) fn apply_pixel(&self, x: i32, y: i32) -> (i32, i32) {
+        let (x, y) = self.apply(x as f32 + 0.5, y as f32 + 0.5);
+        (x.floor() as i32, y.floor() as i32)
+    }
+
+    pub(crate) fn rect_corners(&self, rect: Rect) -> [(f32, f32); 4] {
+        let (left, top) = (rect.x as f32, rect.y as f32);
+        let (right, bottom) = (rect.right() as f32, rect.bottom() as f32);
+        [
+            self.apply(left, top),
+            self.apply(right, top),
+            self.apply(right, bottom),
+            self.apply(left, bottom),
+        ]
+    }
+
+    // pixels whose centers lie in the bounding box of the transformed rect,
+    // exactly the rect's pixels for axis aligned transforms
+    pub(crate) fn bounding_rect(&self, rect: Rect) -> Rect {
+        let corners = self.rect_corners(rect);
+        let (mut min, mut max) = (corners[0], corners[0]);
+        for &(x, y) in &corners[1..] {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+        let left = (min.0 - 0.5).ceil() as i32;
+        let top = (min.1 - 0.5).ceil() as i32;
+        let right = ((max.0 - 0.5).ceil() as i32).max(left);
+        let bottom = ((max.1 - 0.5).ceil() as i32).max(top);
+        Rect::new(left, top, span(left, right), span(top, bottom))
+    }
+
+    fn determinant(&self) -> f32 {
+        self.xx * self.yy - self.xy * self.yx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        canvas::{
+            tests::{buffer, painted},
+            Canvas,
+        },
+        color::Color,
+        dither::Dither,
+        format::PixelFormat,
+        sprite::Image,
+    };
+
+    const RED: Color = Color::rgb(255, 0, 0);
+
+    #[test]
+    fn bounding_rect_of_axis_aligned_transforms() {
+        let rect = Rect::new(1, 2, 3, 4);
+        assert_eq!(Transform::IDENTITY.bounding_rect(rect), rect);
+        assert_eq!(
+            Transform::translate(-1.0, 2.0).bounding_rect(rect),
+            Rect::new(0, 4, 3, 4)
+        );
+        assert_eq!(
+            Transform::scale(2.0, 0.5).bounding_rect(rect),
+            Rect::new(2, 1, 6, 2)
+        );
+    }
+
+    #[test]
+    fn bounding_rect_saturates_at_the_i32_range() {
+        let whole = Rect::new(i32::MIN, i32::MIN, u32::MAX, u32::MAX);
+        assert_eq!(Transform::IDENTITY.bounding_rect(whole), whole);
+        let far = Transform::scale(1e10, 1e10).bounding_rect(Rect::new(-1, -1, 2, 2));
+        assert_eq!(far, whole);
+    }
+
+    #[test]
+    fn drawing_is_clipped_to_the_clip_rect() {
+        let mut pixels = buffer(4, 4, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 4, 4);
+        canvas.with_clip(Rect::new(1, 1, 2, 5), |canvas| {
+            canvas.clear(RED);
+            assert_eq!(canvas.clip(), Rect::new(1, 1, 2, 3));
+        });
+        assert_eq!(canvas.clip(), canvas.bounds());
+        assert_eq!(
+            painted(&canvas, RED),
+            [(1, 1), (2, 1), (1, 2), (2, 2), (1, 3), (2, 3)]
+        );
+    }
+
+    #[test]
+    fn drawing_far_outside_does_not_overflow() {
+        let mut pixels = buffer(4, 4, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 4, 4);
+        let whole = Rect::new(i32::MIN, i32::MIN, u32::MAX, u32::MAX);
+        canvas.with_clip(whole, |canvas| {
+            canvas.fill_rect(Rect::new(i32::MIN, 0, u32::MAX, 1), RED);
+            canvas.dither_rect(whole, &[Color::BLACK, RED], Dither::FloydSteinberg);
+        });
+        canvas.with_transform(Transform::scale(1e10, 1e10), |canvas| {
+            canvas.fill_rect(Rect::new(-1, -1, 2, 2), RED);
+        });
+        assert_eq!(painted(&canvas, RED).len(), 16);
+
+        let mut pixels = buffer(4, 4, PixelFormat::Rgba8Srgb);
+        let mut canvas = Canvas::new(&mut pixels, 4, 4);
+        let mut image = Image::new(2, 2);
+        image.canvas().clear(RED);
+        canvas.with_transform(Transform::translate(1.0, 0.0), |canvas| {
+            canvas.set_pixel(i32::MAX, 0, RED);
+            canvas.blit(&image, i32::MAX, 0);
+        });
+        assert_eq!(canvas.dirty_rect(), None);
+    }
+}
@@ -0,0 +1,53 @@
+use w_pixbuf::{color::Color, config::Config, init::State, rect::Rect};
+
+const SIZE: u32 = 8;
+
+// RGBA bytes of a pixel in a frame read back from the GPU
+fn pixel(frame: &[u8], x: u32, y: u32) -> [u8; 4] {
+    let start = ((y * SIZE + x) * 4) as usize;
+    frame[start..start + 4].try_into().unwrap()
+}
+
+// sRGB round trips through the GPU may be off by one
+fn assert_close(actual: [u8; 4], expected: [u8; 4]) {
+    let close = actual
+        .iter()
+        .zip(expected)
+        .all(|(&a, e)| a.abs_diff(e) <= 1);
+    assert!(close, "expected {expected:?}, got {actual:?}");
+}
+
+// machines without any graphics adapter can opt out with this variable
+// instead of the test passing without rendering anything
+const SKIP_VAR: &str = "W_PIXBUF_SKIP_GPU_TESTS";
+
+#[test]
+fn rendered_frame_shows_the_buffer() {
+    if std::env::var_os(SKIP_VAR).is_some() {
+        eprintln!("skipped, {SKIP_VAR} is set");
+        return;
+    }
+    let config = Config::new()
+        .with_window_size(SIZE, SIZE)
+        .with_buffer_size(SIZE, SIZE);
+    let mut state = match pollster::block_on(State::new_headless(&config)) {
+        Ok(state) => state,
+        Err(err) => panic!("creating a headless state failed: {err}, set {SKIP_VAR} to skip"),
+    };
+
+    state.draw(|canvas| {
+        canvas.clear(Color::BLACK);
+        canvas.fill_rect(Rect::new(0, 0, SIZE / 2, SIZE), Color::rgb(255, 0, 0));
+        canvas.set_pixel(SIZE as i32 - 1, SIZE as i32 - 1, Color::rgb(0, 0, 255));
+    });
+    state.render().expect("rendering failed");
+    let frame = state
+        .read_frame()
+        .expect("headless states can be read back");
+
+    assert_eq!(frame.len(), (SIZE * SIZE * 4) as usize);
+    assert_close(pixel(&frame, 0, 0), [255, 0, 0, 255]);
+    assert_close(pixel(&frame, SIZE / 2 - 1, SIZE - 1), [255, 0, 0, 255]);
+    assert_close(pixel(&frame, SIZE / 2, 0), [0, 0, 0, 255]);
+    assert_close(pixel(&frame, SIZE - 1, SIZE - 1), [0, 0, 255, 255]);
+}